@@ -13,9 +13,9 @@ async fn main() -> EcoPrintResult<()> {
         .filter_level(log::LevelFilter::Trace)
         .init();
 
-    let repeated_commands = std::iter::repeat(ESCPOSDataBuilder::Command(ESCPOSCommand::LineFeed))
-        .take(5)
-        .collect::<Vec<_>>();
+    let repeated_commands =
+        std::iter::repeat_n(ESCPOSDataBuilder::Command(ESCPOSCommand::LineFeed), 5)
+            .collect::<Vec<_>>();
 
     let mut commands: ESCPOSBuilder = ESCPOSBuilder::default();
     commands.add_commands(vec![
@@ -28,8 +28,8 @@ async fn main() -> EcoPrintResult<()> {
     // Add repeated commands
     commands.add_commands(repeated_commands);
 
-
-    let mut printer = ESCPOSPrinterBLE::new()?;
+    // Runs on the #[tokio::main] runtime, no nested runtime is created
+    let mut printer = ESCPOSPrinterBLE::from_current()?;
     printer.start().await?;
     printer.scan().await?;
     let mut device_printer: Option<Peripheral> = None;
@@ -48,6 +48,6 @@ async fn main() -> EcoPrintResult<()> {
         }
     }
     printer.connect(device_printer.clone().unwrap()).await?;
-    printer.print(commands.to_escpos()).await?;
+    printer.print_bytes(commands.to_escpos_bytes()).await?;
     Ok(())
 }
@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+/// Connection state of the peripheral held by ESCPOSPrinterBLE.
+/// Every transition is published to the subscribers of ESCPOSPrinterBLE::connection_events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// No peripheral connected, or the peripheral was disconnected on purpose
+    Disconnected,
+    /// First connection to a peripheral is in progress
+    Connecting,
+    /// Peripheral connected and ready to print
+    Connected,
+    /// Peripheral dropped the connection and a reconnect attempt is in progress
+    Reconnecting { attempt: u32 },
+    /// Connect or reconnect gave up, a new call to connect is needed
+    Failed,
+}
+
+/// Exponential backoff used to reconnect a peripheral that dropped the connection.
+/// The reconnect happens before the next print, so the caller doesn't need to watch the state.
+/// e.g.:
+/// ```rust
+/// use std::time::Duration;
+/// use eco_print::ble::ReconnectPolicy;
+///
+/// let policy = ReconnectPolicy {
+///     initial_delay: Duration::from_millis(200),
+///     max_attempts: Some(3),
+///     ..Default::default()
+/// };
+/// assert_eq!(policy.delay_for(1), Duration::from_millis(200));
+/// assert_eq!(policy.delay_for(2), Duration::from_millis(400));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    /// If false, a dropped connection is reported as Failed on the next print
+    pub enabled: bool,
+    /// Delay before the first attempt
+    pub initial_delay: Duration,
+    /// Upper bound for the delay between attempts
+    pub max_delay: Duration,
+    /// Factor applied to the delay after each failed attempt
+    pub multiplier: f64,
+    /// Attempts before giving up, None retries forever
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            max_attempts: Some(5),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Policy that never tries to reconnect
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Default::default()
+        }
+    }
+
+    /// Delay to wait before the attempt number `attempt` (starting at 1)
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1) as i32;
+        let delay = self.initial_delay.as_secs_f64() * self.multiplier.max(1.0).powi(exponent);
+        Duration::from_secs_f64(delay.min(self.max_delay.as_secs_f64()))
+    }
+
+    /// Check if the attempt number `attempt` (starting at 1) is allowed by the policy
+    pub fn allows(&self, attempt: u32) -> bool {
+        self.enabled && self.max_attempts.is_none_or(|max| attempt <= max)
+    }
+}
@@ -0,0 +1,133 @@
+use btleplug::api::{PeripheralProperties, ScanFilter};
+use regex::Regex;
+use uuid::Uuid;
+
+use crate::{EcoPrintError, EcoPrintResult};
+
+use super::THERMAL_PRINTER_SERVICE;
+
+/// Name prefixes advertised by common thermal printers (Xprinter, Goojprt, Rongta, Sunmi, ...)
+pub const KNOWN_PRINTER_NAME_PREFIXES: &[&str] = &[
+    "Printer",
+    "BlueTooth Printer",
+    "InnerPrinter",
+    "MPT",
+    "MTP",
+    "PT-",
+    "RPP",
+    "XP-",
+];
+
+/// Filter applied to the devices found on a BLE scan.
+/// The services are also sent to the adapter, the other fields are checked on each discovered device.
+/// e.g.:
+/// ```rust
+/// use eco_print::ble::BLEScanFilter;
+///
+/// let filter = BLEScanFilter::default()
+///     .with_name_prefix("XP-")
+///     .with_min_rssi(-80);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BLEScanFilter {
+    /// Only devices advertising at least one of these services
+    pub services: Vec<Uuid>,
+    /// Only devices whose local name starts with this prefix
+    pub name_prefix: Option<String>,
+    /// Only devices whose local name matches this regex
+    pub name_regex: Option<Regex>,
+    /// Only devices with a signal at least this strong (dBm)
+    pub min_rssi: Option<i16>,
+    /// Only devices that look like a thermal printer, by service or by KNOWN_PRINTER_NAME_PREFIXES
+    pub known_printers_only: bool,
+}
+
+impl BLEScanFilter {
+    pub fn with_service(mut self, service: Uuid) -> Self {
+        self.services.push(service);
+        self
+    }
+
+    pub fn with_name_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.name_prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn with_name_regex(mut self, pattern: &str) -> EcoPrintResult<Self> {
+        let regex = Regex::new(pattern).map_err(|_err| {
+            EcoPrintError::BLEScan(format!("Invalid name regex {} - {}", pattern, _err))
+        })?;
+        self.name_regex = Some(regex);
+        Ok(self)
+    }
+
+    pub fn with_min_rssi(mut self, rssi: i16) -> Self {
+        self.min_rssi = Some(rssi);
+        self
+    }
+
+    pub fn known_printers_only(mut self) -> Self {
+        self.known_printers_only = true;
+        self
+    }
+
+    /// Part of the filter the adapter can apply itself
+    pub fn to_scan_filter(&self) -> ScanFilter {
+        ScanFilter {
+            services: self.services.clone(),
+        }
+    }
+
+    /// Check if a discovered device passes the filter
+    pub fn matches(&self, properties: &PeripheralProperties) -> bool {
+        let name = properties.local_name.as_deref();
+
+        // Some adapters ignore the ScanFilter, so the services are checked again
+        if !self.services.is_empty()
+            && !self
+                .services
+                .iter()
+                .any(|service| properties.services.contains(service))
+        {
+            return false;
+        }
+
+        if let Some(prefix) = &self.name_prefix {
+            if !name.is_some_and(|name| name.starts_with(prefix.as_str())) {
+                return false;
+            }
+        }
+
+        if let Some(regex) = &self.name_regex {
+            if !name.is_some_and(|name| regex.is_match(name)) {
+                return false;
+            }
+        }
+
+        if let Some(min_rssi) = self.min_rssi {
+            if properties.rssi.is_none_or(|rssi| rssi < min_rssi) {
+                return false;
+            }
+        }
+
+        if self.known_printers_only && !Self::is_known_printer(properties) {
+            return false;
+        }
+
+        true
+    }
+
+    /// Check if the device advertises the thermal printer service or a known printer name
+    pub fn is_known_printer(properties: &PeripheralProperties) -> bool {
+        let has_printer_service = properties
+            .services
+            .iter()
+            .any(|service| service.to_string() == THERMAL_PRINTER_SERVICE);
+        let has_printer_name = properties.local_name.as_deref().is_some_and(|name| {
+            KNOWN_PRINTER_NAME_PREFIXES
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+        });
+        has_printer_service || has_printer_name
+    }
+}
@@ -1,24 +1,37 @@
-use std::sync::{
-    atomic::{AtomicUsize, Ordering},
-    Arc,
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 
+mod connection;
+mod filter;
+mod runtime;
+pub use connection::{ConnectionState, ReconnectPolicy};
+pub use filter::{BLEScanFilter, KNOWN_PRINTER_NAME_PREFIXES};
+use runtime::BLERuntime;
+
 use btleplug::{
     api::{
-        bleuuid::uuid_from_u16, Central, CentralEvent, Manager as ManagerTrait,
-        Peripheral as PeripheralTrait, ScanFilter, WriteType,
+        bleuuid::uuid_from_u16, Central, CentralEvent, CharPropFlags, Characteristic,
+        Manager as ManagerTrait, Peripheral as PeripheralTrait, ScanFilter, WriteType,
     },
     platform::{Adapter, Manager, Peripheral},
 };
 use futures_lite::StreamExt;
 use tokio::{
-    runtime::{Builder, Runtime},
-    sync::Mutex,
-    task,
+    runtime::{Builder, Handle, Runtime},
+    sync::{broadcast, mpsc, Mutex},
 };
 use uuid::Uuid;
 
-use crate::{EcoPrintError, EcoPrintResult, FinderTrait, PrinterTrait};
+use crate::{
+    status::{AsbDemux, Incoming, PrinterStatus, StatusTrait, DEFAULT_STATUS_TIMEOUT},
+    EcoPrintError, EcoPrintResult, FinderTrait, PrinterHandle, PrinterId, PrinterTrait,
+    TransportTrait,
+};
 
 // Android
 #[cfg(target_os = "android")]
@@ -30,28 +43,409 @@ std::thread_local! {
     static JNI_ENV: RefCell<Option<AttachGuard<'static>>> = RefCell::new(None);
 }
 
-pub const THERMAL_PRINTER_SERVICE: &'static str = "000018f0-0000-1000-8000-00805f9b34fb";
+pub const THERMAL_PRINTER_SERVICE: &str = "000018f0-0000-1000-8000-00805f9b34fb";
 pub const THERMAL_PRINTER_CHR_0: Uuid = uuid_from_u16(0x2af0);
 pub const THERMAL_PRINTER_CHR_1: Uuid = uuid_from_u16(0x2af1);
+/// Time given to a peripheral to answer a connect or reconnect attempt
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// BLE thermal printer.
+/// The btleplug tasks run on a tokio runtime that is either owned by the printer (new)
+/// or owned by the caller (with_handle, from_current).
 pub struct ESCPOSPrinterBLE {
-    _runtime: tokio::runtime::Runtime,
+    _runtime: BLERuntime,
     _ble_shared_data: Arc<Mutex<BLESharedData>>,
+    _reconnect_policy: ReconnectPolicy,
+    _connect_timeout: Duration,
+    _refuse_when_paper_out: bool,
+    _replies: Option<mpsc::UnboundedReceiver<Vec<u8>>>,
+    /// Connection the replies are subscribed on
+    _replies_connection: u64,
+    _status_events: broadcast::Sender<PrinterStatus>,
 }
 
-#[derive(Default)]
 struct BLESharedData {
     _device: Option<Peripheral>,
     _adapter: Option<Adapter>,
     _devices: Vec<Peripheral>,
+    _scan_filter: BLEScanFilter,
+    _state: ConnectionState,
+    _state_events: broadcast::Sender<ConnectionState>,
+    /// Incremented on each connection and reconnection, the peripheral drops the subscriptions of the previous one
+    _connection: u64,
+}
+
+impl Default for BLESharedData {
+    fn default() -> Self {
+        let (_state_events, _) = broadcast::channel(16);
+        Self {
+            _device: None,
+            _adapter: None,
+            _devices: Vec::new(),
+            _scan_filter: BLEScanFilter::default(),
+            _state: ConnectionState::Disconnected,
+            _state_events,
+            _connection: 0,
+        }
+    }
+}
+
+impl BLESharedData {
+    fn set_state(&mut self, state: ConnectionState) {
+        if self._state == state {
+            return;
+        }
+        log::info!("Connection state: {:?} -> {:?}", self._state, state);
+        self._state = state;
+        // No receivers is not an error, nobody is listening the events
+        let _ = self._state_events.send(state);
+    }
 }
 
 impl ESCPOSPrinterBLE {
+    /// Create the printer with its own runtime (2 worker threads).
+    /// The runtime is shut down in background when the printer is dropped.
     pub fn new() -> EcoPrintResult<Self> {
-        Ok(Self {
-            _runtime: Self::_create_runtime()?,
+        Ok(Self::_with_runtime(BLERuntime::Owned(Some(
+            Self::_create_runtime()?,
+        ))))
+    }
+
+    /// Create the printer on a runtime owned by the caller, no runtime is created.
+    /// The caller must keep the runtime alive while the printer is used.
+    /// On Android the runtime threads must be attached to the JVM by the caller.
+    pub fn with_handle(handle: Handle) -> Self {
+        Self::_with_runtime(BLERuntime::Shared(handle))
+    }
+
+    /// Create the printer on the runtime of the current async context.
+    /// Fails if it is not called from inside a tokio runtime.
+    pub fn from_current() -> EcoPrintResult<Self> {
+        let handle =
+            Handle::try_current().map_err(|_err| EcoPrintError::Runtime(_err.to_string()))?;
+        Ok(Self::with_handle(handle))
+    }
+
+    fn _with_runtime(runtime: BLERuntime) -> Self {
+        Self {
+            _runtime: runtime,
             _ble_shared_data: Arc::new(Mutex::new(BLESharedData::default())),
-        })
+            _reconnect_policy: ReconnectPolicy::default(),
+            _connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            _refuse_when_paper_out: false,
+            _replies: None,
+            _replies_connection: 0,
+            _status_events: broadcast::channel(16).0,
+        }
+    }
+
+    /// Set the policy used to reconnect the peripheral when it drops the connection
+    pub fn set_reconnect_policy(&mut self, policy: ReconnectPolicy) {
+        self._reconnect_policy = policy;
+    }
+
+    /// Set the time given to the peripheral to answer each connect or reconnect attempt
+    pub fn set_connect_timeout(&mut self, timeout: Duration) {
+        self._connect_timeout = timeout;
+    }
+
+    /// Refuse to print when the roll paper end sensor is set.
+    /// The paper is checked with DLE EOT 4 before each print, printers that don't reply are printed anyway.
+    pub fn set_refuse_when_paper_out(&mut self, refuse: bool) {
+        self._refuse_when_paper_out = refuse;
+    }
+
+    /// Set the filter used by the next scans.
+    /// Only the devices that pass the filter are returned by get_devices
+    pub async fn set_scan_filter(&mut self, filter: BLEScanFilter) {
+        self._ble_shared_data.lock().await._scan_filter = filter;
+    }
+
+    /// Get the current connection state
+    pub async fn connection_state(&self) -> ConnectionState {
+        self._ble_shared_data.lock().await._state
+    }
+
+    /// Subscribe to the connection state changes.
+    /// Each call returns a new receiver that gets the states set after the call.
+    pub async fn connection_events(&self) -> broadcast::Receiver<ConnectionState> {
+        self._ble_shared_data.lock().await._state_events.subscribe()
+    }
+
+    /// Build the handle to store in the app settings for a discovered peripheral
+    pub async fn handle_of(device: &Peripheral) -> PrinterHandle {
+        let name = match device.properties().await {
+            Ok(Some(properties)) => properties.local_name,
+            _ => None,
+        };
+        PrinterHandle {
+            id: PrinterId::ble(Self::_ble_address(device)),
+            name,
+        }
+    }
+
+    /// Get the handle of the connected peripheral
+    pub async fn connected_handle(&self) -> Option<PrinterHandle> {
+        let device = self._ble_shared_data.lock().await._device.clone()?;
+        Some(Self::handle_of(&device).await)
+    }
+
+    fn _ble_address(device: &Peripheral) -> String {
+        // CoreBluetooth hides the MAC address, the peripheral UUID is the stable id there
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        let address = device.id().to_string();
+        #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+        let address = device.address().to_string();
+        address.to_uppercase()
+    }
+
+    async fn _find_peripheral(
+        adapter: &Adapter,
+        address: &str,
+    ) -> EcoPrintResult<Option<Peripheral>> {
+        let devices = adapter.peripherals().await.map_err(|_err| {
+            EcoPrintError::BLEScan(format!("Error on discovery devices - {}", _err))
+        })?;
+        Ok(devices
+            .into_iter()
+            .find(|device| Self::_ble_address(device) == address))
+    }
+
+    /// Write the bytes on the printer characteristic, reconnecting the peripheral if needed
+    async fn _write_bytes(&mut self, data: Vec<u8>) -> EcoPrintResult<()> {
+        let runtime = self._runtime.handle();
+        let ble_shared_data_clone = Arc::clone(&self._ble_shared_data);
+        let reconnect_policy = self._reconnect_policy.clone();
+        let connect_timeout = self._connect_timeout;
+
+        runtime
+            .spawn({
+                let ble_shared_data_clone = Arc::clone(&ble_shared_data_clone);
+                async move {
+                    let device = Self::_ensure_connected(
+                        ble_shared_data_clone,
+                        reconnect_policy,
+                        connect_timeout,
+                    )
+                    .await?;
+                    let chr =
+                        Self::_characteristic(&device, |chr| chr.uuid == THERMAL_PRINTER_CHR_1)
+                            .await?;
+
+                    device
+                        .write(&chr, &data, WriteType::WithoutResponse)
+                        .await
+                        .map_err(|_err| {
+                            EcoPrintError::Printing(format!(
+                                "Peripheral characteristic write/printing - {}",
+                                _err
+                            ))
+                        })?;
+                    Ok::<(), EcoPrintError>(())
+                }
+            })
+            .await
+            .map_err(|_err| EcoPrintError::Runtime(_err.to_string()))?
+    }
+
+    /// Reconnect the peripheral with the policy backoff if it dropped the connection
+    async fn _reconnect_if_needed(&self) -> EcoPrintResult<()> {
+        let ble_shared_data_clone = Arc::clone(&self._ble_shared_data);
+        let reconnect_policy = self._reconnect_policy.clone();
+        let connect_timeout = self._connect_timeout;
+        self._runtime
+            .handle()
+            .spawn(async move {
+                Self::_ensure_connected(ble_shared_data_clone, reconnect_policy, connect_timeout)
+                    .await?;
+                Ok::<(), EcoPrintError>(())
+            })
+            .await
+            .map_err(|_err| EcoPrintError::Runtime(_err.to_string()))?
+    }
+
+    /// Subscribe to the printer notifications if not subscribed on the current connection
+    /// and return the replies receiver
+    async fn _ensure_replies(&mut self) -> EcoPrintResult<&mut mpsc::UnboundedReceiver<Vec<u8>>> {
+        let connection = self._ble_shared_data.lock().await._connection;
+        if self._replies.is_some() && self._replies_connection != connection {
+            log::info!("Peripheral reconnected, subscribing to the notifications again");
+            self._replies = None;
+        }
+        if self._replies.is_none() {
+            let runtime = self._runtime.handle().clone();
+            let ble_shared_data_clone = Arc::clone(&self._ble_shared_data);
+            let reconnect_policy = self._reconnect_policy.clone();
+            let connect_timeout = self._connect_timeout;
+            let status_events = self._status_events.clone();
+
+            let (replies, connection) = runtime
+                .clone()
+                .spawn(async move {
+                    let device = Self::_ensure_connected(
+                        Arc::clone(&ble_shared_data_clone),
+                        reconnect_policy,
+                        connect_timeout,
+                    )
+                    .await?;
+                    let connection = ble_shared_data_clone.lock().await._connection;
+                    // 0x2af0 is the notify characteristic of most printers, others use any notify one
+                    let chr = match Self::_characteristic(&device, |chr| {
+                        chr.uuid == THERMAL_PRINTER_CHR_0
+                            && chr.properties.contains(CharPropFlags::NOTIFY)
+                    })
+                    .await
+                    {
+                        Ok(chr) => chr,
+                        Err(_) => {
+                            Self::_characteristic(&device, |chr| {
+                                chr.properties.contains(CharPropFlags::NOTIFY)
+                            })
+                            .await?
+                        }
+                    };
+
+                    device.subscribe(&chr).await.map_err(|_err| {
+                        EcoPrintError::BLEServices(format!(
+                            "Error on subscribe to {} - {}",
+                            chr.uuid, _err
+                        ))
+                    })?;
+                    let mut notifications = device.notifications().await.map_err(|_err| {
+                        EcoPrintError::BLEServices(format!(
+                            "Error on notifications stream - {}",
+                            _err
+                        ))
+                    })?;
+
+                    let (sender, receiver) = mpsc::unbounded_channel();
+                    runtime.spawn(async move {
+                        let mut demux = AsbDemux::default();
+                        let mut last_status = None;
+                        while let Some(notification) = notifications.next().await {
+                            if notification.uuid != chr.uuid {
+                                continue;
+                            }
+                            for incoming in demux.push(&notification.value) {
+                                match incoming {
+                                    Incoming::Asb(status) => {
+                                        if last_status == Some(status) {
+                                            continue;
+                                        }
+                                        last_status = Some(status);
+                                        log::info!("Automatic status back: {:?}", status);
+                                        // No receivers is not an error, nobody is listening the events
+                                        let _ = status_events.send(status);
+                                    }
+                                    Incoming::Reply(reply) => {
+                                        if sender.send(reply).is_err() {
+                                            return;
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    });
+                    Ok::<_, EcoPrintError>((receiver, connection))
+                })
+                .await
+                .map_err(|_err| EcoPrintError::Runtime(_err.to_string()))??;
+            self._replies = Some(replies);
+            self._replies_connection = connection;
+        }
+        Ok(self._replies.as_mut().unwrap())
+    }
+
+    async fn _characteristic(
+        device: &Peripheral,
+        predicate: impl Fn(&Characteristic) -> bool,
+    ) -> EcoPrintResult<Characteristic> {
+        device.discover_services().await.map_err(|_err| {
+            EcoPrintError::BLEConnect(format!("Discover services on connect - {}", _err))
+        })?;
+        device
+            .characteristics()
+            .into_iter()
+            .find(|chr| predicate(chr))
+            .ok_or_else(|| {
+                EcoPrintError::BLEServices("Printer characteristic not found".to_string())
+            })
+    }
+
+    /// Subscribe to the status changes sent by the printer.
+    /// The printer only sends them after StatusTrait::set_automatic_status, e.g.:
+    /// ```rust,no_run
+    /// # use eco_print::{ble::ESCPOSPrinterBLE, status::{AsbMask, StatusTrait}};
+    /// # async fn example(printer: &mut ESCPOSPrinterBLE) -> eco_print::EcoPrintResult<()> {
+    /// let mut events = printer.status_events().await?;
+    /// printer.set_automatic_status(AsbMask::ONLINE | AsbMask::PAPER).await?;
+    /// while let Ok(status) = events.recv().await {
+    ///     println!("paper out: {}", status.paper_out);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn status_events(&mut self) -> EcoPrintResult<broadcast::Receiver<PrinterStatus>> {
+        self._ensure_replies().await?;
+        Ok(self._status_events.subscribe())
+    }
+
+    /// Return the connected peripheral, reconnecting it with the policy backoff if it dropped the connection
+    async fn _ensure_connected(
+        ble_shared_data: Arc<Mutex<BLESharedData>>,
+        policy: ReconnectPolicy,
+        connect_timeout: Duration,
+    ) -> EcoPrintResult<Peripheral> {
+        let device = ble_shared_data
+            .lock()
+            .await
+            ._device
+            .clone()
+            .ok_or_else(|| EcoPrintError::BLEPeripheral("Peripheral not connected".to_string()))?;
+
+        if device.is_connected().await.unwrap_or(false) {
+            ble_shared_data
+                .lock()
+                .await
+                .set_state(ConnectionState::Connected);
+            return Ok(device);
+        }
+
+        let mut attempt = 1;
+        while policy.allows(attempt) {
+            ble_shared_data
+                .lock()
+                .await
+                .set_state(ConnectionState::Reconnecting { attempt });
+            tokio::time::sleep(policy.delay_for(attempt)).await;
+
+            match tokio::time::timeout(connect_timeout, device.connect()).await {
+                Ok(Ok(())) if device.is_connected().await.unwrap_or(false) => {
+                    let mut shared_guard = ble_shared_data.lock().await;
+                    shared_guard._connection += 1;
+                    shared_guard.set_state(ConnectionState::Connected);
+                    log::info!("Peripheral reconnected on attempt {}", attempt);
+                    return Ok(device);
+                }
+                Ok(Ok(())) => log::warn!("Reconnect attempt {} did not connect", attempt),
+                Ok(Err(_err)) => log::warn!("Reconnect attempt {} failed - {}", attempt, _err),
+                Err(_) => log::warn!(
+                    "Reconnect attempt {} got no answer after {:?}",
+                    attempt,
+                    connect_timeout
+                ),
+            }
+            attempt += 1;
+        }
+
+        ble_shared_data
+            .lock()
+            .await
+            .set_state(ConnectionState::Failed);
+        Err(EcoPrintError::BLEConnect(format!(
+            "Peripheral disconnected, gave up after {} reconnect attempts",
+            attempt - 1
+        )))
     }
 
     fn _create_runtime() -> EcoPrintResult<Runtime> {
@@ -126,126 +520,134 @@ impl ESCPOSPrinterBLE {
 
 impl PrinterTrait for ESCPOSPrinterBLE {
     async fn print(&mut self, data: String) -> EcoPrintResult<()> {
-        let runtime = &self._runtime;
-        let ble_shared_data_clone = Arc::clone(&self._ble_shared_data);
+        self.print_bytes(data.into_bytes()).await
+    }
 
-        runtime
-            .spawn({
-                let ble_shared_data_clone = Arc::clone(&ble_shared_data_clone);
-                async move {
-                    if let Some(device) = &ble_shared_data_clone.lock().await._device {
-                        device.discover_services().await.map_err(|_err| {
-                            EcoPrintError::BLEConnect(format!(
-                                "Discover services on connect - {}",
-                                _err.to_string()
-                            ))
-                        })?;
-                        let chrs = device.characteristics();
-
-                        let chr = chrs
-                            .iter()
-                            .find(|chr| chr.uuid == THERMAL_PRINTER_CHR_1)
-                            .expect("Characteristic not found")
-                            .clone();
-
-                        device
-                            .write(&chr, data.as_bytes(), WriteType::WithoutResponse)
-                            .await
-                            .map_err(|_err| {
-                                EcoPrintError::Printing(format!(
-                                    "Peripheral characteristic write/printing - {}",
-                                    _err.to_string()
-                                ))
-                            })?;
-                    } else {
-                        return Err(EcoPrintError::BLEPeripheral(format!(
-                            "Peripheral not connected"
-                        )));
-                    }
-                    Ok::<(), EcoPrintError>(())
-                }
-            })
-            .await
-            .map_err(|_err| EcoPrintError::Runtime(_err.to_string()))?
+    async fn print_bytes(&mut self, data: Vec<u8>) -> EcoPrintResult<()> {
+        if self._refuse_when_paper_out {
+            match self.check_paper(DEFAULT_STATUS_TIMEOUT).await {
+                Err(EcoPrintError::PaperOut) => return Err(EcoPrintError::PaperOut),
+                Err(_err) => log::warn!("Paper status not available, printing anyway - {}", _err),
+                Ok(()) => {}
+            }
+        }
+        self._write_bytes(data).await
+    }
+}
+
+impl TransportTrait for ESCPOSPrinterBLE {
+    async fn write(&mut self, data: &[u8]) -> EcoPrintResult<()> {
+        // A reconnect drops the subscription, so it's done before subscribing again
+        self._reconnect_if_needed().await?;
+        // The replies must be subscribed before the write, or a fast reply is lost
+        let replies = self._ensure_replies().await?;
+        // Anything received before the write can't be a reply to it
+        while replies.try_recv().is_ok() {}
+        self._write_bytes(data.to_vec()).await
+    }
+
+    async fn read(&mut self, timeout: Duration) -> EcoPrintResult<Vec<u8>> {
+        let replies = self._ensure_replies().await?;
+        match tokio::time::timeout(timeout, replies.recv()).await {
+            Ok(Some(bytes)) => Ok(bytes),
+            Ok(None) => {
+                // Notifications stop when the peripheral disconnects, the next call subscribes again
+                self._replies = None;
+                Err(EcoPrintError::BLEPeripheral(
+                    "Notifications stream closed".to_string(),
+                ))
+            }
+            Err(_) => Err(EcoPrintError::Timeout(format!(
+                "No reply from peripheral after {:?}",
+                timeout
+            ))),
+        }
     }
 }
 
 impl FinderTrait<Peripheral> for ESCPOSPrinterBLE {
     async fn start(&mut self) -> crate::EcoPrintResult<()> {
-        let runtime = &self._runtime;
+        let runtime = self._runtime.handle();
         let ble_shared_data_clone = Arc::clone(&self._ble_shared_data);
 
-        let _ = runtime
+        runtime
             .spawn({
                 let ble_shared_data_clone = Arc::clone(&ble_shared_data_clone);
                 async move {
                     let manager = Manager::new().await.map_err(|_err| {
-                        EcoPrintError::BLEAdapter(format!(
-                            "Error creating manager - {}",
-                            _err.to_string()
-                        ))
+                        EcoPrintError::BLEAdapter(format!("Error creating manager - {}", _err))
                     })?;
                     let adapter = manager
                         .adapters()
                         .await
                         .map_err(|_err| {
-                            EcoPrintError::BLEAdapter(format!(
-                                "Error getting adapters - {}",
-                                _err.to_string()
-                            ))
+                            EcoPrintError::BLEAdapter(format!("Error getting adapters - {}", _err))
                         })?
                         .into_iter()
-                        .nth(0)
-                        .unwrap();
+                        .next()
+                        .ok_or_else(|| EcoPrintError::BLEAdapter("No adapter found".to_string()))?;
                     ble_shared_data_clone.lock().await._adapter = Some(adapter.clone());
 
                     Ok::<(), EcoPrintError>(())
                 }
             })
             .await
-            .map_err(|_err| EcoPrintError::Runtime(_err.to_string()))?;
+            .map_err(|_err| EcoPrintError::Runtime(_err.to_string()))??;
 
         let adapter = ble_shared_data_clone.lock().await._adapter.clone().unwrap();
         let mut events = adapter.events().await.map_err(|_err| {
-            EcoPrintError::BLEAdapter(format!(
-                "Error getting events adapter - {}",
-                _err.to_string()
-            ))
+            EcoPrintError::BLEAdapter(format!("Error getting events adapter - {}", _err))
         })?;
 
         let ble_shared_data_clone = Arc::clone(&ble_shared_data_clone);
 
-        let _ = task::spawn(async move {
+        runtime.spawn(async move {
             while let Some(event) = events.next().await {
                 match event {
                     CentralEvent::DeviceDiscovered(_) => {
                         let mut devices_guard = ble_shared_data_clone.lock().await;
                         devices_guard._devices.clear();
                         let _devices = adapter.peripherals().await.map_err(|_err| {
-                            EcoPrintError::BLEScan(format!(
-                                "Error on discovery devices - {}",
-                                _err.to_string()
-                            ))
+                            EcoPrintError::BLEScan(format!("Error on discovery devices - {}", _err))
                         });
                         match _devices {
                             Ok(_devices) => {
                                 for device in _devices {
-                                    let d_name = device
-                                        .properties()
-                                        .await
-                                        .unwrap()
-                                        .unwrap()
-                                        .local_name
-                                        .unwrap_or("Unknown".to_string());
+                                    let Ok(Some(properties)) = device.properties().await else {
+                                        continue;
+                                    };
+                                    if !devices_guard._scan_filter.matches(&properties) {
+                                        continue;
+                                    }
+                                    let d_name =
+                                        properties.local_name.unwrap_or("Unknown".to_string());
                                     log::info!("Device discovered: {}", d_name);
                                     devices_guard._devices.push(device);
                                 }
                             }
                             Err(_err) => {
-                                log::error!("Error on discovery devices - {}", _err.to_string());
+                                log::error!("Error on discovery devices - {}", _err);
                             }
                         }
                     }
+                    CentralEvent::DeviceConnected(id) => {
+                        let mut shared_guard = ble_shared_data_clone.lock().await;
+                        let is_our_device =
+                            shared_guard._device.as_ref().is_some_and(|d| d.id() == id);
+                        // Reconnects are driven by _ensure_connected, which sets the state itself
+                        if is_our_device && shared_guard._state == ConnectionState::Disconnected {
+                            shared_guard.set_state(ConnectionState::Connected);
+                        }
+                    }
+                    CentralEvent::DeviceDisconnected(id) => {
+                        let mut shared_guard = ble_shared_data_clone.lock().await;
+                        let is_our_device =
+                            shared_guard._device.as_ref().is_some_and(|d| d.id() == id);
+                        if is_our_device && shared_guard._state == ConnectionState::Connected {
+                            log::warn!("Peripheral dropped the connection");
+                            shared_guard.set_state(ConnectionState::Disconnected);
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -255,7 +657,33 @@ impl FinderTrait<Peripheral> for ESCPOSPrinterBLE {
     }
 
     async fn scan(&mut self) -> crate::EcoPrintResult<()> {
-        let runtime = &self._runtime;
+        let runtime = self._runtime.handle();
+        let ble_shared_data_clone = Arc::clone(&self._ble_shared_data);
+        runtime
+            .spawn({
+                let ble_shared_data_clone = Arc::clone(&ble_shared_data_clone);
+                async move {
+                    let mut shared_guard = ble_shared_data_clone.lock().await;
+                    // Devices from a previous scan may not pass the current filter
+                    shared_guard._devices.clear();
+                    let scan_filter = shared_guard._scan_filter.to_scan_filter();
+                    let _adapter = shared_guard._adapter.clone();
+                    // Released while scanning, the discovered devices are added with the lock
+                    drop(shared_guard);
+                    if let Some(adapter) = _adapter {
+                        adapter.start_scan(scan_filter).await.map_err(|_err| {
+                            EcoPrintError::BLEScan(format!("Error scan - {}", _err))
+                        })?;
+                    }
+                    Ok::<(), EcoPrintError>(())
+                }
+            })
+            .await
+            .map_err(|_err| EcoPrintError::Runtime(_err.to_string()))?
+    }
+
+    async fn stop_scan(&mut self) -> crate::EcoPrintResult<()> {
+        let runtime = self._runtime.handle();
         let ble_shared_data_clone = Arc::clone(&self._ble_shared_data);
         runtime
             .spawn({
@@ -263,12 +691,9 @@ impl FinderTrait<Peripheral> for ESCPOSPrinterBLE {
                 async move {
                     let _adapter = ble_shared_data_clone.lock().await._adapter.clone();
                     if let Some(adapter) = _adapter {
-                        adapter
-                            .start_scan(ScanFilter::default())
-                            .await
-                            .map_err(|_err| {
-                                EcoPrintError::BLEScan(format!("Error scan - {}", _err.to_string()))
-                            })?;
+                        adapter.stop_scan().await.map_err(|_err| {
+                            EcoPrintError::BLEScan(format!("Error stop scan - {}", _err))
+                        })?;
                     }
                     Ok::<(), EcoPrintError>(())
                 }
@@ -280,76 +705,173 @@ impl FinderTrait<Peripheral> for ESCPOSPrinterBLE {
     async fn get_devices(&self) -> Vec<Peripheral> {
         self._ble_shared_data.lock().await._devices.clone()
     }
+
     async fn connect(&mut self, device: Peripheral) -> crate::EcoPrintResult<()> {
-        let runtime = &self._runtime;
+        let runtime = self._runtime.handle();
         let ble_shared_data_clone = Arc::clone(&self._ble_shared_data);
+        let connect_timeout = self._connect_timeout;
         runtime
             .spawn({
                 let ble_shared_data_clone = Arc::clone(&ble_shared_data_clone);
                 async move {
-                    let mut _device_guard = ble_shared_data_clone.lock().await;
-                    let mut _device = &_device_guard._device;
-                    if let Some(device) = _device {
-                        if device.is_connected().await.map_err(|_err| {
-                            EcoPrintError::BLEPeripheral(format!(
-                                "Error on check if device is connected - {}",
-                                _err.to_string()
-                            ))
-                        })? {
-                            log::info!("Peripheral already connected");
-                        } else {
-                            log::info!("Peripheral not connected and is setted, trying to connect");
-                            device.connect().await.map_err(|_err| {
+                    let current = ble_shared_data_clone.lock().await._device.clone();
+                    if let Some(current) = current {
+                        if current.id() == device.id()
+                            && current.is_connected().await.map_err(|_err| {
                                 EcoPrintError::BLEPeripheral(format!(
-                                    "Error on connect to device - {}",
-                                    _err.to_string()
+                                    "Error on check if device is connected - {}",
+                                    _err
                                 ))
-                            })?;
+                            })?
+                        {
+                            log::info!("Peripheral already connected");
+                            ble_shared_data_clone
+                                .lock()
+                                .await
+                                .set_state(ConnectionState::Connected);
+                            return Ok::<(), EcoPrintError>(());
                         }
-                        return Ok::<(), EcoPrintError>(());
                     }
+
                     log::info!("Peripheral not connected, trying to connect");
-                    device.connect().await.map_err(|_err| {
-                        EcoPrintError::BLEPeripheral(format!(
-                            "Error on connect to device - {}",
-                            _err.to_string()
-                        ))
-                    })?;
-                    let connected = device.is_connected().await.map_err(|_err| {
-                        EcoPrintError::BLEPeripheral(format!(
-                            "Error on check if device is connected - {}",
-                            _err.to_string()
-                        ))
-                    })?;
+                    ble_shared_data_clone
+                        .lock()
+                        .await
+                        .set_state(ConnectionState::Connecting);
+                    // The shared data isn't locked while connecting, so the state can be read meanwhile
+                    let connected =
+                        match tokio::time::timeout(connect_timeout, device.connect()).await {
+                            Ok(Ok(())) => device.is_connected().await.map_err(|_err| {
+                                EcoPrintError::BLEPeripheral(format!(
+                                    "Error on check if device is connected - {}",
+                                    _err
+                                ))
+                            }),
+                            Ok(Err(_err)) => Err(EcoPrintError::BLEPeripheral(format!(
+                                "Error on connect to device - {}",
+                                _err
+                            ))),
+                            Err(_) => Err(EcoPrintError::Timeout(format!(
+                                "No answer from peripheral after {:?}",
+                                connect_timeout
+                            ))),
+                        };
 
-                    if connected {
-                        _device_guard._device = Some(device);
-                        log::info!("Peripheral connected");
+                    let mut _device_guard = ble_shared_data_clone.lock().await;
+                    match connected {
+                        Ok(true) => {
+                            _device_guard._device = Some(device);
+                            _device_guard._connection += 1;
+                            _device_guard.set_state(ConnectionState::Connected);
+                            log::info!("Peripheral connected");
+                            Ok(())
+                        }
+                        Ok(false) => {
+                            _device_guard.set_state(ConnectionState::Failed);
+                            Err(EcoPrintError::BLEConnect(
+                                "Peripheral did not report connected".to_string(),
+                            ))
+                        }
+                        Err(_err) => {
+                            _device_guard.set_state(ConnectionState::Failed);
+                            Err(_err)
+                        }
                     }
-                    Ok::<(), EcoPrintError>(())
                 }
             })
             .await
             .map_err(|_err| EcoPrintError::Runtime(_err.to_string()))?
     }
 
+    async fn connect_by_id(
+        &mut self,
+        id: &PrinterId,
+        timeout: Duration,
+    ) -> crate::EcoPrintResult<PrinterHandle> {
+        let PrinterId::Ble { address } = id else {
+            return Err(EcoPrintError::PrinterId(format!(
+                "{} is not a Bluetooth/BLE id",
+                id
+            )));
+        };
+        let address = address.to_uppercase();
+        let runtime = self._runtime.handle();
+        let ble_shared_data_clone = Arc::clone(&self._ble_shared_data);
+
+        let device = runtime
+            .spawn({
+                let ble_shared_data_clone = Arc::clone(&ble_shared_data_clone);
+                async move {
+                    let adapter = ble_shared_data_clone
+                        .lock()
+                        .await
+                        ._adapter
+                        .clone()
+                        .ok_or_else(|| {
+                            EcoPrintError::BLEAdapter(
+                                "Adapter not started, call start before connect_by_id".to_string(),
+                            )
+                        })?;
+
+                    // The adapter remembers peripherals from previous scans and pairings
+                    if let Some(device) = Self::_find_peripheral(&adapter, &address).await? {
+                        return Ok::<Peripheral, EcoPrintError>(device);
+                    }
+
+                    log::info!(
+                        "Peripheral {} not known, starting a short discovery",
+                        address
+                    );
+                    adapter
+                        .start_scan(ScanFilter::default())
+                        .await
+                        .map_err(|_err| EcoPrintError::BLEScan(format!("Error scan - {}", _err)))?;
+                    let found = tokio::time::timeout(timeout, async {
+                        loop {
+                            if let Some(device) = Self::_find_peripheral(&adapter, &address).await?
+                            {
+                                return Ok::<Peripheral, EcoPrintError>(device);
+                            }
+                            tokio::time::sleep(Duration::from_millis(250)).await;
+                        }
+                    })
+                    .await;
+                    if let Err(_err) = adapter.stop_scan().await {
+                        log::warn!("Error stop scan - {}", _err);
+                    }
+
+                    found.map_err(|_| {
+                        EcoPrintError::BLEScan(format!(
+                            "Peripheral {} not found after {:?}",
+                            address, timeout
+                        ))
+                    })?
+                }
+            })
+            .await
+            .map_err(|_err| EcoPrintError::Runtime(_err.to_string()))??;
+
+        let handle = Self::handle_of(&device).await;
+        self.connect(device).await?;
+        Ok(handle)
+    }
+
     async fn disconnect(&mut self) -> crate::EcoPrintResult<()> {
-        let device = &self._ble_shared_data.lock().await._device;
+        let shared_guard = self._ble_shared_data.lock().await;
 
-        if let None = device {
+        let Some(dvc) = shared_guard._device.clone() else {
             log::info!("Peripheral already disconnected");
             return Ok(());
-        }
+        };
+        // Released while disconnecting, the adapter events (e.g.: DeviceDisconnected) need the lock
+        drop(shared_guard);
 
-        if let Some(dvc) = device {
-            dvc.disconnect().await.map_err(|_err| {
-                EcoPrintError::BLEPeripheral(format!(
-                    "Error on disconnect from device - {}",
-                    _err.to_string()
-                ))
-            })?;
-            self._ble_shared_data.lock().await._device = None;
-        }
+        dvc.disconnect().await.map_err(|_err| {
+            EcoPrintError::BLEPeripheral(format!("Error on disconnect from device - {}", _err))
+        })?;
+        let mut shared_guard = self._ble_shared_data.lock().await;
+        shared_guard._device = None;
+        shared_guard.set_state(ConnectionState::Disconnected);
 
         Ok(())
     }
@@ -0,0 +1,31 @@
+use tokio::runtime::{Handle, Runtime};
+
+/// Runtime where ESCPOSPrinterBLE runs the btleplug tasks (scan, discovery events, connect, print).
+pub(crate) enum BLERuntime {
+    /// Runtime created by ESCPOSPrinterBLE::new, shut down when the printer is dropped
+    Owned(Option<Runtime>),
+    /// Runtime owned by the caller, the printer only spawns tasks on it
+    Shared(Handle),
+}
+
+impl BLERuntime {
+    pub(crate) fn handle(&self) -> &Handle {
+        match self {
+            BLERuntime::Owned(Some(runtime)) => runtime.handle(),
+            BLERuntime::Shared(handle) => handle,
+            BLERuntime::Owned(None) => unreachable!("owned runtime is only taken on drop"),
+        }
+    }
+}
+
+impl Drop for BLERuntime {
+    fn drop(&mut self) {
+        // Dropping a Runtime blocks until its tasks finish, which panics inside an async context.
+        // shutdown_background doesn't wait, so the printer can be dropped anywhere.
+        if let BLERuntime::Owned(runtime) = self {
+            if let Some(runtime) = runtime.take() {
+                runtime.shutdown_background();
+            }
+        }
+    }
+}
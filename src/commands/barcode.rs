@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{json::BarcodeJson, EcoPrintError, EcoPrintResult};
+
+/// GS k m - Barcode symbology, the values are the m of the format with the data length
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BarcodeSystem {
+    UpcA = 65,
+    UpcE = 66,
+    Ean13 = 67,
+    Ean8 = 68,
+    Code39 = 69,
+    Itf = 70,
+    Codabar = 71,
+    Code93 = 72,
+    Code128 = 73,
+}
+
+/// GS H n - Position of the human readable interpretation (HRI) characters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HriPosition {
+    None = 0,
+    Above = 1,
+    #[default]
+    Below = 2,
+    Both = 3,
+}
+
+/// Barcode printed with GS k.
+/// Code128 data without a code set prefix is printed with code set B (`{B`).
+/// e.g.:
+/// ```rust
+/// use eco_print::commands::barcode::{Barcode, BarcodeSystem};
+///
+/// let barcode = Barcode::new(BarcodeSystem::Ean13, "4006381333931").unwrap();
+/// assert!(Barcode::new(BarcodeSystem::Ean13, "40063813339X").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "BarcodeJson")]
+pub struct Barcode {
+    pub system: BarcodeSystem,
+    data: String,
+    /// Bar height in dots
+    pub height: u8,
+    /// Width of the narrow bar in dots, 2 to 6
+    pub module_width: u8,
+    pub hri: HriPosition,
+}
+
+impl Barcode {
+    pub fn new(system: BarcodeSystem, data: impl Into<String>) -> EcoPrintResult<Self> {
+        let mut data: String = data.into();
+        if system == BarcodeSystem::Code128 && !data.starts_with('{') {
+            data.insert_str(0, "{B");
+        }
+
+        let digits = data.chars().all(|c| c.is_ascii_digit());
+        let len = data.len();
+        let valid = match system {
+            BarcodeSystem::UpcA => digits && (11..=12).contains(&len),
+            BarcodeSystem::UpcE => digits && matches!(len, 6..=8 | 11..=12),
+            BarcodeSystem::Ean13 => digits && (12..=13).contains(&len),
+            BarcodeSystem::Ean8 => digits && (7..=8).contains(&len),
+            BarcodeSystem::Itf => digits && len >= 2 && len.is_multiple_of(2),
+            BarcodeSystem::Code39 => {
+                len >= 1
+                    && data.chars().all(|c| {
+                        c.is_ascii_digit() || c.is_ascii_uppercase() || " $%*+-./".contains(c)
+                    })
+            }
+            BarcodeSystem::Codabar => {
+                len >= 2
+                    && data
+                        .chars()
+                        .all(|c| c.is_ascii_digit() || "ABCDabcd$+-./:".contains(c))
+            }
+            BarcodeSystem::Code93 | BarcodeSystem::Code128 => len >= 1 && data.is_ascii(),
+        };
+        if !valid || len > 255 {
+            return Err(EcoPrintError::Barcode(format!(
+                "Invalid data {:?} for {:?}",
+                data, system
+            )));
+        }
+        Ok(Self {
+            system,
+            data,
+            height: 80,
+            module_width: 3,
+            hri: HriPosition::Below,
+        })
+    }
+
+    pub fn with_height(mut self, height: u8) -> Self {
+        self.height = height.max(1);
+        self
+    }
+
+    pub fn with_module_width(mut self, module_width: u8) -> Self {
+        self.module_width = module_width.clamp(2, 6);
+        self
+    }
+
+    pub fn with_hri(mut self, hri: HriPosition) -> Self {
+        self.hri = hri;
+        self
+    }
+
+    pub fn data(&self) -> &str {
+        &self.data
+    }
+
+    /// GS h, GS w, GS H and GS k
+    pub fn to_escpos_bytes(&self) -> Vec<u8> {
+        let mut escpos = vec![0x1D, 0x68, self.height];
+        escpos.extend([0x1D, 0x77, self.module_width]);
+        escpos.extend([0x1D, 0x48, self.hri as u8]);
+        escpos.extend([0x1D, 0x6B, self.system as u8, self.data.len() as u8]);
+        escpos.extend(self.data.as_bytes());
+        escpos
+    }
+}
@@ -0,0 +1,215 @@
+use unicode_bidi::BidiInfo;
+
+/// Visual order of a line with right-to-left text (Hebrew, Arabic), as printers only print left to right.
+/// Arabic letters are shaped first, with the presentation form of their position in the word
+/// (isolated, final, initial or medial) and the lam-alef ligatures.
+/// Returns the line and if its paragraph direction is right to left.
+/// e.g.:
+/// ```rust
+/// use eco_print::commands::bidi::to_visual;
+///
+/// assert_eq!(to_visual("Total: 12.50"), ("Total: 12.50".to_string(), false));
+/// assert_eq!(to_visual("שלום (2)"), ("(2) םולש".to_string(), true));
+/// assert_eq!(to_visual("شاي"), ("\u{FEF1}\u{FE8E}\u{FEB7}".to_string(), true));
+/// ```
+pub fn to_visual(line: &str) -> (String, bool) {
+    if !line.chars().any(is_rtl_char) {
+        return (line.to_string(), false);
+    }
+    let shaped = shape_arabic(line);
+    let bidi = BidiInfo::new(&shaped, None);
+    let Some(paragraph) = bidi.paragraphs.first() else {
+        return (shaped, false);
+    };
+    let rtl = paragraph.level.is_rtl();
+    let (levels, runs) = bidi.visual_runs(paragraph, paragraph.range.clone());
+    let mut visual = String::with_capacity(shaped.len());
+    for run in runs {
+        let text = &shaped[run.clone()];
+        if levels[run.start].is_rtl() {
+            visual.extend(text.chars().rev().map(mirror));
+        } else {
+            visual.push_str(text);
+        }
+    }
+    (visual, rtl)
+}
+
+fn is_rtl_char(c: char) -> bool {
+    matches!(c, '\u{0590}'..='\u{08FF}' | '\u{FB1D}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}')
+}
+
+/// Brackets are drawn the other way in right-to-left runs
+fn mirror(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '«' => '»',
+        '»' => '«',
+        c => c,
+    }
+}
+
+/// Arabic letter, its isolated presentation form and how many forms it has:
+/// 1 doesn't join, 2 joins the previous letter only (isolated, final),
+/// 4 joins both sides (isolated, final, initial, medial)
+const ARABIC_FORMS: [(char, u32, u32); 36] = [
+    ('\u{0621}', 0xFE80, 1),
+    ('\u{0622}', 0xFE81, 2),
+    ('\u{0623}', 0xFE83, 2),
+    ('\u{0624}', 0xFE85, 2),
+    ('\u{0625}', 0xFE87, 2),
+    ('\u{0626}', 0xFE89, 4),
+    ('\u{0627}', 0xFE8D, 2),
+    ('\u{0628}', 0xFE8F, 4),
+    ('\u{0629}', 0xFE93, 2),
+    ('\u{062A}', 0xFE95, 4),
+    ('\u{062B}', 0xFE99, 4),
+    ('\u{062C}', 0xFE9D, 4),
+    ('\u{062D}', 0xFEA1, 4),
+    ('\u{062E}', 0xFEA5, 4),
+    ('\u{062F}', 0xFEA9, 2),
+    ('\u{0630}', 0xFEAB, 2),
+    ('\u{0631}', 0xFEAD, 2),
+    ('\u{0632}', 0xFEAF, 2),
+    ('\u{0633}', 0xFEB1, 4),
+    ('\u{0634}', 0xFEB5, 4),
+    ('\u{0635}', 0xFEB9, 4),
+    ('\u{0636}', 0xFEBD, 4),
+    ('\u{0637}', 0xFEC1, 4),
+    ('\u{0638}', 0xFEC5, 4),
+    ('\u{0639}', 0xFEC9, 4),
+    ('\u{063A}', 0xFECD, 4),
+    ('\u{0641}', 0xFED1, 4),
+    ('\u{0642}', 0xFED5, 4),
+    ('\u{0643}', 0xFED9, 4),
+    ('\u{0644}', 0xFEDD, 4),
+    ('\u{0645}', 0xFEE1, 4),
+    ('\u{0646}', 0xFEE5, 4),
+    ('\u{0647}', 0xFEE9, 4),
+    ('\u{0648}', 0xFEED, 2),
+    ('\u{0649}', 0xFEEF, 2),
+    ('\u{064A}', 0xFEF1, 4),
+];
+
+/// Alef of the lam-alef ligatures and the isolated form of the ligature, the final form follows it
+const LAM_ALEF: [(char, u32); 4] = [
+    ('\u{0622}', 0xFEF5),
+    ('\u{0623}', 0xFEF7),
+    ('\u{0625}', 0xFEF9),
+    ('\u{0627}', 0xFEFB),
+];
+
+const LAM: char = '\u{0644}';
+/// Tatweel and the zero width joiner join both sides without forms of their own
+const JOINERS: [char; 2] = ['\u{0640}', '\u{200D}'];
+
+/// Isolated form and number of forms of the letter
+fn arabic_forms(c: char) -> Option<(u32, u32)> {
+    ARABIC_FORMS
+        .iter()
+        .find(|(letter, _, _)| *letter == c)
+        .map(|(_, isolated, forms)| (*isolated, *forms))
+}
+
+/// Isolated form of the lam-alef ligature with the alef
+fn lam_alef(alef: char) -> Option<u32> {
+    LAM_ALEF
+        .iter()
+        .find(|(letter, _)| *letter == alef)
+        .map(|(_, isolated)| *isolated)
+}
+
+/// Harakat and other marks are skipped when joining the letters around them
+fn is_transparent(c: char) -> bool {
+    matches!(c, '\u{064B}'..='\u{065F}' | '\u{0670}')
+}
+
+/// The letter joins the next one
+fn joins_next(c: char) -> bool {
+    JOINERS.contains(&c) || matches!(arabic_forms(c), Some((_, 4)))
+}
+
+/// The letter joins the previous one
+fn joins_previous(c: char) -> bool {
+    JOINERS.contains(&c) || matches!(arabic_forms(c), Some((_, forms)) if forms >= 2)
+}
+
+/// Replace the Arabic letters with their contextual presentation forms, in logical order
+pub fn shape_arabic(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let neighbour = |from: usize, step: isize| -> Option<char> {
+        let mut i = from as isize + step;
+        while i >= 0 && (i as usize) < chars.len() {
+            let c = chars[i as usize];
+            if !is_transparent(c) {
+                return Some(c);
+            }
+            i += step;
+        }
+        None
+    };
+
+    let mut shaped = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let Some((isolated, forms)) = arabic_forms(c) else {
+            shaped.push(c);
+            i += 1;
+            continue;
+        };
+        let previous = neighbour(i, -1).is_some_and(joins_next) && joins_previous(c);
+        let next_char = neighbour(i, 1);
+
+        // Lam followed by an alef is a single ligature, which joins the previous letter only
+        if let Some(ligature) = chars.get(i + 1).copied().and_then(lam_alef) {
+            if c == LAM {
+                shaped.extend(char::from_u32(ligature + u32::from(previous)));
+                i += 2;
+                continue;
+            }
+        }
+
+        let next = forms == 4 && next_char.is_some_and(joins_previous);
+        let offset = match (previous, next) {
+            (false, false) => 0,
+            (true, false) => 1,
+            (false, true) => 2,
+            (true, true) => 3,
+        };
+        shaped.extend(char::from_u32(isolated + offset));
+        i += 1;
+    }
+    shaped
+}
+
+/// Less specific form of an Arabic presentation form, for the code pages without all the forms:
+/// medial to initial, initial and final to isolated, isolated to the letter (two letters for lam-alef)
+pub(crate) fn fallback_form(c: char) -> Option<String> {
+    let code = u32::from(c);
+    let to_string = |code: u32| char::from_u32(code).map(String::from);
+    for (alef, isolated) in LAM_ALEF {
+        match code.checked_sub(isolated) {
+            Some(1) => return to_string(isolated),
+            // The ligature in visual order, the alef is on the left
+            Some(0) => return Some([alef, LAM].iter().collect()),
+            _ => {}
+        }
+    }
+    for (letter, isolated, forms) in ARABIC_FORMS {
+        match code.checked_sub(isolated) {
+            Some(3) if forms == 4 => return to_string(isolated + 2),
+            Some(offset @ (1 | 2)) if offset < forms => return to_string(isolated),
+            Some(0) => return Some(letter.to_string()),
+            _ => {}
+        }
+    }
+    None
+}
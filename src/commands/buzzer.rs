@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+use crate::profile::BuzzerType;
+
+/// Buzzer beep, rendered with the buzzer command of the printer profile.
+/// Each vendor has its own limits, the values are clamped to them:
+/// - ESC B: 1..9 beeps of 50ms..450ms, no pattern
+/// - Epson ESC ( A: pattern 1..10, 1..63 beeps, 100ms steps
+/// - Star ESC GS BEL: pattern 1 or 2 selects the buzzer circuit, 20ms steps, repeated `count` times
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Beep {
+    pub count: u8,
+    pub duration_ms: u16,
+    pub pattern: u8,
+}
+
+impl Default for Beep {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            duration_ms: 200,
+            pattern: 1,
+        }
+    }
+}
+
+impl Beep {
+    pub fn new(count: u8, duration_ms: u16) -> Self {
+        Self {
+            count,
+            duration_ms,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_pattern(mut self, pattern: u8) -> Self {
+        self.pattern = pattern;
+        self
+    }
+
+    pub fn to_escpos_bytes(&self, buzzer: BuzzerType) -> Vec<u8> {
+        match buzzer {
+            BuzzerType::None => {
+                log::warn!("Printer profile has no buzzer, beep skipped");
+                Vec::new()
+            }
+            BuzzerType::EscB => {
+                let n = self.count.clamp(1, 9);
+                let t = (self.duration_ms / 50).clamp(1, 9) as u8;
+                vec![0x1B, 0x42, n, t]
+            }
+            BuzzerType::Epson => {
+                let pattern = self.pattern.clamp(1, 10);
+                let count = self.count.clamp(1, 63);
+                let t = (self.duration_ms / 100).clamp(1, 255) as u8;
+                vec![0x1B, 0x28, 0x41, 0x04, 0x00, 0x30, pattern, count, t]
+            }
+            BuzzerType::Star => {
+                let circuit = self.pattern.clamp(1, 2);
+                let t = (self.duration_ms / 20).clamp(1, 255) as u8;
+                [0x1B, 0x1D, 0x07, circuit, t, t].repeat(self.count.max(1) as usize)
+            }
+        }
+    }
+}
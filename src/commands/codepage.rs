@@ -0,0 +1,286 @@
+use encoding_rs::Encoding;
+
+use serde::{Deserialize, Serialize};
+
+use super::bidi;
+
+/// Character encoding of the text, the single-byte code page or a double-byte encoding
+/// of the printers sold in Asia (Kanji mode), or UTF-8 on firmware with FS ( C
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextEncoding {
+    /// Code page selected with ESC t
+    #[default]
+    CodePage,
+    /// Simplified Chinese, GB2312/GBK on most firmware
+    Gb18030,
+    /// Traditional Chinese
+    Big5,
+    /// Japanese
+    ShiftJis,
+    /// Korean
+    EucKr,
+    Utf8,
+}
+
+impl TextEncoding {
+    /// Double-byte encoding printed in Kanji mode
+    pub fn double_byte(&self) -> Option<&'static Encoding> {
+        match self {
+            TextEncoding::Gb18030 => Some(encoding_rs::GB18030),
+            TextEncoding::Big5 => Some(encoding_rs::BIG5),
+            TextEncoding::ShiftJis => Some(encoding_rs::SHIFT_JIS),
+            TextEncoding::EucKr => Some(encoding_rs::EUC_KR),
+            TextEncoding::CodePage | TextEncoding::Utf8 => None,
+        }
+    }
+}
+
+/// FS C n - Kanji code system of the Japanese models
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KanjiCodeSystem {
+    Jis = 0,
+    ShiftJis = 1,
+    ShiftJis2004 = 2,
+}
+
+/// Character table used to encode the text sent to the printer, the code page selected with ESC t n
+/// or the double-byte encoding of the Kanji mode.
+/// ASCII is the same on every table, the upper half (0x80..=0xFF) depends on the table.
+/// Code pages that aren't known here only print ASCII.
+/// e.g.:
+/// ```rust
+/// use eco_print::commands::codepage::{CharacterTable, TextEncoding};
+///
+/// let table = CharacterTable::from_code_page(2);
+/// assert_eq!(table.encode("Café"), Ok(b"Caf\x82".to_vec()));
+/// assert_eq!(table.encode("咖啡"), Err(vec!['咖', '啡']));
+///
+/// let table = CharacterTable::from_encoding(TextEncoding::Gb18030, true, 0);
+/// assert_eq!(table.encode("咖啡"), Ok(b"\xBF\xA7\xB7\xC8".to_vec()));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub enum CharacterTable {
+    /// Table without a known mapping, only ASCII
+    Ascii,
+    /// Upper half given char by char (DOS and JIS X 0201 tables)
+    Upper(&'static [char; 128]),
+    /// Single-byte table from the WHATWG encodings (Windows and ISO 8859 tables)
+    Encoding(&'static Encoding),
+    /// Double-byte encoding, ASCII stays on one byte
+    DoubleByte(&'static Encoding),
+    Utf8,
+}
+
+impl CharacterTable {
+    /// Table of the Epson ESC t numbering
+    pub fn from_code_page(code_page: u8) -> Self {
+        match code_page {
+            0 => CharacterTable::Upper(&PC437),
+            1 => CharacterTable::Upper(&KATAKANA),
+            2 => CharacterTable::Upper(&PC850),
+            15 => CharacterTable::Encoding(encoding_rs::ISO_8859_7),
+            16 => CharacterTable::Encoding(encoding_rs::WINDOWS_1252),
+            17 => CharacterTable::Encoding(encoding_rs::IBM866),
+            19 => CharacterTable::Upper(&PC858),
+            36 => CharacterTable::Upper(&PC862),
+            37 => CharacterTable::Upper(&PC864),
+            39 => CharacterTable::Encoding(encoding_rs::ISO_8859_2),
+            40 => CharacterTable::Encoding(encoding_rs::ISO_8859_15),
+            45 => CharacterTable::Encoding(encoding_rs::WINDOWS_1250),
+            46 => CharacterTable::Encoding(encoding_rs::WINDOWS_1251),
+            47 => CharacterTable::Encoding(encoding_rs::WINDOWS_1253),
+            48 => CharacterTable::Encoding(encoding_rs::WINDOWS_1254),
+            49 => CharacterTable::Encoding(encoding_rs::WINDOWS_1255),
+            50 => CharacterTable::Encoding(encoding_rs::WINDOWS_1256),
+            51 => CharacterTable::Encoding(encoding_rs::WINDOWS_1257),
+            52 => CharacterTable::Encoding(encoding_rs::WINDOWS_1258),
+            _ => CharacterTable::Ascii,
+        }
+    }
+
+    /// Table for the text encoding, in Kanji mode or not
+    pub fn from_encoding(encoding: TextEncoding, kanji: bool, code_page: u8) -> Self {
+        if encoding == TextEncoding::Utf8 {
+            return CharacterTable::Utf8;
+        }
+        match encoding.double_byte() {
+            Some(double_byte) if kanji => CharacterTable::DoubleByte(double_byte),
+            _ => Self::from_code_page(code_page),
+        }
+    }
+
+    /// Add the bytes of the char on this table, false when the table doesn't have it.
+    /// Arabic presentation forms missing from the table are replaced by a less specific form.
+    pub fn encode_char(&self, c: char, escpos: &mut Vec<u8>) -> bool {
+        if self.encode_exact(c, escpos) {
+            return true;
+        }
+        let Some(form) = bidi::fallback_form(c) else {
+            return false;
+        };
+        let len = escpos.len();
+        if form
+            .chars()
+            .all(|form_char| self.encode_char(form_char, escpos))
+        {
+            return true;
+        }
+        escpos.truncate(len);
+        false
+    }
+
+    fn encode_exact(&self, c: char, escpos: &mut Vec<u8>) -> bool {
+        if c.is_ascii() {
+            escpos.push(c as u8);
+            return true;
+        }
+        let mut buffer = [0u8; 4];
+        match self {
+            CharacterTable::Ascii => false,
+            CharacterTable::Upper(upper) => match upper.iter().position(|&other| other == c) {
+                Some(index) => {
+                    escpos.push(0x80 + index as u8);
+                    true
+                }
+                None => false,
+            },
+            CharacterTable::Encoding(encoding) => {
+                let (bytes, _, had_errors) = encoding.encode(c.encode_utf8(&mut buffer));
+                match *bytes {
+                    [byte] if !had_errors => {
+                        escpos.push(byte);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            CharacterTable::DoubleByte(encoding) => {
+                let (bytes, _, had_errors) = encoding.encode(c.encode_utf8(&mut buffer));
+                if !had_errors {
+                    escpos.extend(bytes.iter());
+                }
+                !had_errors
+            }
+            CharacterTable::Utf8 => {
+                escpos.extend(c.encode_utf8(&mut buffer).as_bytes());
+                true
+            }
+        }
+    }
+
+    pub fn can_encode(&self, c: char) -> bool {
+        self.encode_char(c, &mut Vec::new())
+    }
+
+    /// Encode the text, or the chars the table doesn't have
+    pub fn encode(&self, text: &str) -> Result<Vec<u8>, Vec<char>> {
+        let mut escpos = Vec::with_capacity(text.len());
+        let mut missing = Vec::new();
+        for c in text.chars() {
+            if !self.encode_char(c, &mut escpos) {
+                missing.push(c);
+            }
+        }
+        if missing.is_empty() {
+            Ok(escpos)
+        } else {
+            Err(missing)
+        }
+    }
+
+    /// Encode the text, the chars the table doesn't have are printed as `?`
+    pub fn encode_lossy(&self, text: &str) -> Vec<u8> {
+        let mut escpos = Vec::with_capacity(text.len());
+        for c in text.chars() {
+            if !self.encode_char(c, &mut escpos) {
+                escpos.push(b'?');
+            }
+        }
+        escpos
+    }
+}
+
+/// PC437 (USA, standard Europe)
+const PC437: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', //
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ', //
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»', //
+    '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐', //
+    '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧', //
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀', //
+    'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩', //
+    '≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{A0}',
+];
+
+/// PC850 (Multilingual)
+const PC850: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', //
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', 'ø', '£', 'Ø', '×', 'ƒ', //
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '®', '¬', '½', '¼', '¡', '«', '»', //
+    '░', '▒', '▓', '│', '┤', 'Á', 'Â', 'À', '©', '╣', '║', '╗', '╝', '¢', '¥', '┐', //
+    '└', '┴', '┬', '├', '─', '┼', 'ã', 'Ã', '╚', '╔', '╩', '╦', '╠', '═', '╬', '¤', //
+    'ð', 'Ð', 'Ê', 'Ë', 'È', 'ı', 'Í', 'Î', 'Ï', '┘', '┌', '█', '▄', '¦', 'Ì', '▀', //
+    'Ó', 'ß', 'Ô', 'Ò', 'õ', 'Õ', 'µ', 'þ', 'Þ', 'Ú', 'Û', 'Ù', 'ý', 'Ý', '¯', '´', //
+    '\u{AD}', '±', '‗', '¾', '¶', '§', '÷', '¸', '°', '¨', '·', '¹', '³', '²', '■', '\u{A0}',
+];
+
+/// PC858 (Euro), PC850 with the euro sign in place of the dotless i
+const PC858: [char; 128] = {
+    let mut table = PC850;
+    table[0xD5 - 0x80] = '€';
+    table
+};
+
+/// PC862 (Hebrew), PC437 with the Hebrew letters in place of the accented letters
+const PC862: [char; 128] = {
+    let mut table = PC437;
+    let mut index = 0;
+    while index < 27 {
+        // U+05D0 (א) to U+05EA (ת) on 0x80..=0x9A
+        table[index] = match char::from_u32(0x05D0 + index as u32) {
+            Some(c) => c,
+            None => '\0',
+        };
+        index += 1;
+    }
+    table
+};
+
+/// PC864 (Arabic), with the presentation forms of the letters, '\0' where the byte isn't used
+const PC864: [char; 128] = [
+    '°', '·', '∙', '√', '▒', '─', '│', '┼', '┤', '┬', '├', '┴', '┐', '┌', '└', '┘', //
+    'β', '∞', 'φ', '±', '½', '¼', '≈', '«', '»', '\u{FEF7}', '\u{FEF8}', '\0', '\0', '\u{FEFB}',
+    '\u{FEFC}', '\0', //
+    '\u{A0}', '\u{AD}', '\u{FE82}', '£', '¤', '\u{FE84}', '\0', '\0', '\u{FE8E}', '\u{FE8F}',
+    '\u{FE95}', '\u{FE99}', '\u{060C}', '\u{FE9D}', '\u{FEA1}', '\u{FEA5}', //
+    '\u{0660}', '\u{0661}', '\u{0662}', '\u{0663}', '\u{0664}', '\u{0665}', '\u{0666}', '\u{0667}',
+    '\u{0668}', '\u{0669}', '\u{FED1}', '\u{061B}', '\u{FEB1}', '\u{FEB5}', '\u{FEB9}',
+    '\u{061F}', //
+    '¢', '\u{FE80}', '\u{FE81}', '\u{FE83}', '\u{FE85}', '\u{FECA}', '\u{FE8B}', '\u{FE8D}',
+    '\u{FE91}', '\u{FE93}', '\u{FE97}', '\u{FE9B}', '\u{FE9F}', '\u{FEA3}', '\u{FEA7}',
+    '\u{FEA9}', //
+    '\u{FEAB}', '\u{FEAD}', '\u{FEAF}', '\u{FEB3}', '\u{FEB7}', '\u{FEBB}', '\u{FEBF}', '\u{FEC1}',
+    '\u{FEC5}', '\u{FECB}', '\u{FECF}', '¦', '¬', '÷', '×', '\u{FEC9}', //
+    '\u{0640}', '\u{FED3}', '\u{FED7}', '\u{FEDB}', '\u{FEDF}', '\u{FEE3}', '\u{FEE7}', '\u{FEEB}',
+    '\u{FEED}', '\u{FEEF}', '\u{FEF3}', '\u{FEBD}', '\u{FECC}', '\u{FECE}', '\u{FECD}',
+    '\u{FEE1}', //
+    '\u{FE7D}', '\u{0651}', '\u{FEE5}', '\u{FEE9}', '\u{FEEC}', '\u{FEF0}', '\u{FEF2}', '\u{FED0}',
+    '\u{FED5}', '\u{FEF5}', '\u{FEF6}', '\u{FEDD}', '\u{FED9}', '\u{FEF1}', '■', '\0',
+];
+
+/// JIS X 0201 half-width katakana, the bytes above 0xDF are not used
+const KATAKANA: [char; 128] = {
+    let mut table = ['\0'; 128];
+    let mut index = 0x21;
+    while index < 0x60 {
+        // U+FF61 (。) to U+FF9F (゜) on 0xA1..=0xDF
+        table[index] = match char::from_u32(0xFF40 + index as u32) {
+            Some(c) => c,
+            None => '\0',
+        };
+        index += 1;
+    }
+    table
+};
@@ -1,18 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    json::JobJson,
+    nv_graphics::{self, NvKey},
+    profile::{CutCommand, CutterType, PrinterProfile},
+    EcoPrintError, EcoPrintResult,
+};
+
+use super::{
+    barcode::Barcode,
+    bidi,
+    buzzer::Beep,
+    codepage::{CharacterTable, KanjiCodeSystem, TextEncoding},
+    font::RasterFont,
+    image::ESCPOSImage,
+    page::{PageLayout, PrintDirection},
+    qrcode::ESCPOSQRCode,
+    spacing::Spacing,
+    table::Table,
+    text::{Align, TextBlock, TextState},
+};
+
 pub trait ESCPOSBuilderTrait {
-    fn to_escpos(&self) -> String;
+    // Not Required
+    // The bytes above 0x7F (encoded text, images, command parameters) are replaced by U+FFFD
+    #[deprecated(
+        note = "the bytes above 0x7F are lost, use to_escpos_bytes with PrinterTrait::print_bytes"
+    )]
+    fn to_escpos(&self) -> String {
+        String::from_utf8_lossy(&self.to_escpos_bytes()).into_owned()
+    }
+
+    // Raw bytes to send to the printer with PrinterTrait::print_bytes
+    fn to_escpos_bytes(&self) -> Vec<u8>;
 
     // Not Required
     fn add_command(&mut self, _cmd: ESCPOSDataBuilder) {}
-    
+
     // Not Required
     fn add_commands(&mut self, _cmds: Vec<ESCPOSDataBuilder>) {}
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ESCPOSCommand {
     LineFeed,
     FontBold,
     FontNormal,
+    /// ESC - 1 - Underline 1 dot thick, UnderlineMode selects the thickness
     Underline,
     Cut,
     AlignLeft,
@@ -28,81 +63,702 @@ pub enum ESCPOSCommand {
     DoubleWidthOff,
     UpsideDownOn,
     UpsideDownOff,
+    /// ESC - 0 - Underline off
+    UnderlineOff,
+    /// GS ! n - Character width and height multipliers, 1 to 8
+    CharacterSize {
+        width: u8,
+        height: u8,
+    },
+    /// ESC p m t1 t2 - Drawer pulse, on/off times in ms (2ms steps, up to 510ms)
+    DrawerKick {
+        pin: DrawerPin,
+        on_ms: u16,
+        off_ms: u16,
+    },
+    /// ESC @ - Clear the print buffer and reset every setting to the power-on default
+    Initialize,
+    /// ESC t n - Select the character code table
+    CodePage(u8),
+    /// ESC 2 - Default line spacing (about 3.75mm)
+    LineSpacingDefault,
+    /// ESC 3 n - Line spacing in dots
+    LineSpacing(u8),
+    /// GS L nL nH - Left margin in dots
+    LeftMargin(u16),
+    /// GS W nL nH - Print area width in dots, from the left margin
+    PrintAreaWidth(u16),
+    /// ESC SP n - Space on the right of each character in dots
+    CharacterSpacing(u8),
+    /// GS V 1 - Partial cut (one point left uncut)
+    PartialCut,
+    /// GS V 65/66 n - Feed to the cutting position plus `feed` dots and cut
+    FeedAndCut {
+        mode: CutMode,
+        feed: u8,
+    },
+    /// ESC i (full) / ESC m (partial) - Cut on legacy firmware without GS V
+    LegacyCut(CutMode),
+    /// ESC J n - Print the buffer and feed n dots
+    FeedDots(u8),
+    /// DLE DC4 1 m t - Real-time drawer pulse, executed even when the printer is offline
+    /// or the buffer is full (100ms steps, 100ms..800ms)
+    DrawerKickRealTime {
+        pin: DrawerPin,
+        pulse_ms: u16,
+    },
+    /// GS ( L fn=69 - Print the graphic stored in NV memory with the key, scale 1 or 2
+    PrintNvGraphic {
+        key: NvKey,
+        scale_x: u8,
+        scale_y: u8,
+    },
+    /// FS p n 0 - Print the legacy NV bit image number n (starting at 1)
+    PrintNvBitImage(u8),
+    /// ESC L - Enter page mode, the data is kept in the page buffer until printed
+    PageModeOn,
+    /// ESC S - Back to standard mode, the page buffer is discarded
+    StandardModeOn,
+    /// ESC W - Page mode print area in dots, from the upper left corner of the page
+    PrintArea {
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    },
+    /// ESC T n - Page mode print direction
+    PrintDirection(PrintDirection),
+    /// ESC $ nL nH - Absolute position in dots from the start of the line
+    AbsolutePosition(u16),
+    /// GS $ nL nH - Page mode absolute vertical position in dots from the start of the area
+    AbsoluteVerticalPosition(u16),
+    /// FF - Print the page and go back to standard mode
+    PrintPage,
+    /// ESC FF - Print the page and stay in page mode
+    PrintPageKeep,
+    /// CAN - Discard the data of the current print area in page mode
+    CancelPage,
+    /// FS & - Kanji mode on, the bytes above 0x7F are read as double-byte characters
+    KanjiModeOn,
+    /// FS . - Kanji mode off, back to the code page
+    KanjiModeOff,
+    /// FS C n - Kanji code system of the Japanese models
+    KanjiCodeSystem(KanjiCodeSystem),
+    /// ESC - n - Underline off, 1 dot or 2 dots thick
+    UnderlineMode(UnderlineMode),
+    /// GS B 1 - White on black printing
+    ReverseOn,
+    /// GS B 0
+    ReverseOff,
+    /// ESC G 1 - Double-strike, each dot is printed twice for darker text
+    DoubleStrikeOn,
+    /// ESC G 0
+    DoubleStrikeOff,
+    /// ESC V 1 - Characters rotated 90° clockwise
+    Rotate90On,
+    /// ESC V 0
+    Rotate90Off,
+    /// GS b 1 - Smoothing of the characters printed at 4x size or more
+    SmoothingOn,
+    /// GS b 0
+    SmoothingOff,
+    /// ESC M 2 - Font C, only on some models
+    FontC,
+    /// ESC R n - International character set, replaces some ASCII symbols (#, $, @, [, \, ], ...).
+    /// The text is still encoded as ASCII for them.
+    InternationalCharset(InternationalCharset),
+    /// Select how the text is encoded with FS ( C fn=48: UTF-8 (m=2), or the 1-byte encoding (m=1)
+    /// followed by FS . for the code page and FS & for the double-byte encodings (FS C 1 before it for Shift_JIS).
+    Encoding(TextEncoding),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CutMode {
+    Full,
+    Partial,
+}
+
+/// ESC - n - Underline thickness
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnderlineMode {
+    Off = 0,
+    OneDot = 1,
+    TwoDots = 2,
+}
+
+/// ESC R n - International character set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InternationalCharset {
+    Usa = 0,
+    France = 1,
+    Germany = 2,
+    Uk = 3,
+    DenmarkI = 4,
+    Sweden = 5,
+    Italy = 6,
+    SpainI = 7,
+    Japan = 8,
+    Norway = 9,
+    DenmarkII = 10,
+    SpainII = 11,
+    LatinAmerica = 12,
+    Korea = 13,
+    SloveniaCroatia = 14,
+    China = 15,
+    Vietnam = 16,
+    Arabia = 17,
+}
+
+/// Drawer kick-out connector pin driving the drawer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DrawerPin {
+    Pin2 = 0,
+    Pin5 = 1,
+}
+
+impl ESCPOSCommand {
+    /// Drawer pulse with the on/off times that work on most drawers (50ms on, 500ms off)
+    pub fn drawer_kick(pin: DrawerPin) -> Self {
+        ESCPOSCommand::DrawerKick {
+            pin,
+            on_ms: 50,
+            off_ms: 500,
+        }
+    }
 }
 
 impl ESCPOSBuilderTrait for ESCPOSCommand {
-    fn to_escpos(&self) -> String {
-        let escpos = match *self {
-            ESCPOSCommand::LineFeed => "\n",
-            ESCPOSCommand::FontBold => "\x1B\x45\x01",
-            ESCPOSCommand::FontNormal => "\x1B\x45\x00",
-            ESCPOSCommand::Underline => "\x1B\x2D\x01",
-            ESCPOSCommand::Cut => "\x1D\x56\x00",
-            ESCPOSCommand::AlignLeft => "\x1B\x61\x00",
-            ESCPOSCommand::AlignCenter => "\x1B\x61\x01",
-            ESCPOSCommand::AlignRight => "\x1B\x61\x02",
-            ESCPOSCommand::FontA => "\x1B\x4D\x00",
-            ESCPOSCommand::FontB => "\x1B\x4D\x01",
-            ESCPOSCommand::EmphasizeOn => "\x1B\x45\x01",
-            ESCPOSCommand::EmphasizeOff => "\x1B\x45\x00",
-            ESCPOSCommand::DoubleHeightOn => "\x1B\x21\x10",
-            ESCPOSCommand::DoubleHeightOff => "\x1B\x21\x00",
-            ESCPOSCommand::DoubleWidthOn => "\x1B\x21\x20",
-            ESCPOSCommand::DoubleWidthOff => "\x1B\x21\x00",
-            ESCPOSCommand::UpsideDownOn => "\x1B\x7B\x01",
-            ESCPOSCommand::UpsideDownOff => "\x1B\x7B\x00",
+    fn to_escpos_bytes(&self) -> Vec<u8> {
+        let escpos: &[u8] = match *self {
+            ESCPOSCommand::LineFeed => b"\n",
+            ESCPOSCommand::FontBold => b"\x1B\x45\x01",
+            ESCPOSCommand::FontNormal => b"\x1B\x45\x00",
+            ESCPOSCommand::Underline => b"\x1B\x2D\x01",
+            ESCPOSCommand::Cut => b"\x1D\x56\x00",
+            ESCPOSCommand::AlignLeft => b"\x1B\x61\x00",
+            ESCPOSCommand::AlignCenter => b"\x1B\x61\x01",
+            ESCPOSCommand::AlignRight => b"\x1B\x61\x02",
+            ESCPOSCommand::FontA => b"\x1B\x4D\x00",
+            ESCPOSCommand::FontB => b"\x1B\x4D\x01",
+            ESCPOSCommand::EmphasizeOn => b"\x1B\x45\x01",
+            ESCPOSCommand::EmphasizeOff => b"\x1B\x45\x00",
+            ESCPOSCommand::DoubleHeightOn => b"\x1B\x21\x10",
+            ESCPOSCommand::DoubleHeightOff => b"\x1B\x21\x00",
+            ESCPOSCommand::DoubleWidthOn => b"\x1B\x21\x20",
+            ESCPOSCommand::DoubleWidthOff => b"\x1B\x21\x00",
+            ESCPOSCommand::UpsideDownOn => b"\x1B\x7B\x01",
+            ESCPOSCommand::UpsideDownOff => b"\x1B\x7B\x00",
+            ESCPOSCommand::UnderlineOff => b"\x1B\x2D\x00",
+            ESCPOSCommand::CharacterSize { width, height } => {
+                let n = (width.clamp(1, 8) - 1) << 4 | (height.clamp(1, 8) - 1);
+                return vec![0x1D, 0x21, n];
+            }
+            ESCPOSCommand::Initialize => b"\x1B\x40",
+            ESCPOSCommand::CodePage(n) => return vec![0x1B, 0x74, n],
+            ESCPOSCommand::LineSpacingDefault => b"\x1B\x32",
+            ESCPOSCommand::LineSpacing(n) => return vec![0x1B, 0x33, n],
+            ESCPOSCommand::LeftMargin(dots) => {
+                let [nl, nh] = dots.to_le_bytes();
+                return vec![0x1D, 0x4C, nl, nh];
+            }
+            ESCPOSCommand::PrintAreaWidth(dots) => {
+                let [nl, nh] = dots.to_le_bytes();
+                return vec![0x1D, 0x57, nl, nh];
+            }
+            ESCPOSCommand::CharacterSpacing(n) => return vec![0x1B, 0x20, n],
+            ESCPOSCommand::PartialCut => b"\x1D\x56\x01",
+            ESCPOSCommand::FeedAndCut { mode, feed } => {
+                let m = match mode {
+                    CutMode::Full => 65,
+                    CutMode::Partial => 66,
+                };
+                return vec![0x1D, 0x56, m, feed];
+            }
+            ESCPOSCommand::LegacyCut(CutMode::Full) => b"\x1B\x69",
+            ESCPOSCommand::LegacyCut(CutMode::Partial) => b"\x1B\x6D",
+            ESCPOSCommand::FeedDots(n) => return vec![0x1B, 0x4A, n],
+            ESCPOSCommand::DrawerKick { pin, on_ms, off_ms } => {
+                let t1 = (on_ms / 2).min(255) as u8;
+                let t2 = (off_ms / 2).min(255) as u8;
+                return vec![0x1B, 0x70, pin as u8, t1, t2];
+            }
+            ESCPOSCommand::DrawerKickRealTime { pin, pulse_ms } => {
+                let t = (pulse_ms / 100).clamp(1, 8) as u8;
+                return vec![0x10, 0x14, 0x01, pin as u8, t];
+            }
+            ESCPOSCommand::PrintNvGraphic {
+                key,
+                scale_x,
+                scale_y,
+            } => return nv_graphics::print_nv_graphic(key, scale_x, scale_y),
+            ESCPOSCommand::PrintNvBitImage(n) => return nv_graphics::print_nv_bit_image(n),
+            ESCPOSCommand::PageModeOn => b"\x1B\x4C",
+            ESCPOSCommand::StandardModeOn => b"\x1B\x53",
+            ESCPOSCommand::PrintArea {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                let mut escpos = vec![0x1B, 0x57];
+                for value in [x, y, width, height] {
+                    escpos.extend(value.to_le_bytes());
+                }
+                return escpos;
+            }
+            ESCPOSCommand::PrintDirection(direction) => return vec![0x1B, 0x54, direction as u8],
+            ESCPOSCommand::AbsolutePosition(dots) => {
+                let [nl, nh] = dots.to_le_bytes();
+                return vec![0x1B, 0x24, nl, nh];
+            }
+            ESCPOSCommand::AbsoluteVerticalPosition(dots) => {
+                let [nl, nh] = dots.to_le_bytes();
+                return vec![0x1D, 0x24, nl, nh];
+            }
+            ESCPOSCommand::PrintPage => b"\x0C",
+            ESCPOSCommand::PrintPageKeep => b"\x1B\x0C",
+            ESCPOSCommand::CancelPage => b"\x18",
+            ESCPOSCommand::KanjiModeOn => b"\x1C\x26",
+            ESCPOSCommand::KanjiModeOff => b"\x1C\x2E",
+            ESCPOSCommand::KanjiCodeSystem(system) => return vec![0x1C, 0x43, system as u8],
+            ESCPOSCommand::Encoding(TextEncoding::CodePage) => {
+                b"\x1C\x28\x43\x02\x00\x30\x01\x1C\x2E"
+            }
+            ESCPOSCommand::Encoding(TextEncoding::ShiftJis) => {
+                b"\x1C\x28\x43\x02\x00\x30\x01\x1C\x43\x01\x1C\x26"
+            }
+            ESCPOSCommand::Encoding(TextEncoding::Utf8) => b"\x1C\x28\x43\x02\x00\x30\x02",
+            ESCPOSCommand::Encoding(_) => b"\x1C\x28\x43\x02\x00\x30\x01\x1C\x26",
+            ESCPOSCommand::UnderlineMode(mode) => return vec![0x1B, 0x2D, mode as u8],
+            ESCPOSCommand::ReverseOn => b"\x1D\x42\x01",
+            ESCPOSCommand::ReverseOff => b"\x1D\x42\x00",
+            ESCPOSCommand::DoubleStrikeOn => b"\x1B\x47\x01",
+            ESCPOSCommand::DoubleStrikeOff => b"\x1B\x47\x00",
+            ESCPOSCommand::Rotate90On => b"\x1B\x56\x01",
+            ESCPOSCommand::Rotate90Off => b"\x1B\x56\x00",
+            ESCPOSCommand::SmoothingOn => b"\x1D\x62\x01",
+            ESCPOSCommand::SmoothingOff => b"\x1D\x62\x00",
+            ESCPOSCommand::FontC => b"\x1B\x4D\x02",
+            ESCPOSCommand::InternationalCharset(charset) => return vec![0x1B, 0x52, charset as u8],
         };
-        escpos.to_string()
+        escpos.to_vec()
     }
 }
 
 /// Use this enum to build ESCPOS commands
 /// Command(ESCPOSCommand) - Add ESCPOSCommand
 /// Text(String) - Add Text
-/// Image(path/byte) - Add Image
-/// QrCode(String) - Add QR Code
-#[derive(Debug, Clone)]
+/// Image(ESCPOSImage) - Add Image as a raster bit image
+/// QrCode(ESCPOSQRCode) - Add QR Code, as an image on printers without QR support
+/// Barcode(Barcode) - Add Barcode
+/// Beep(Beep) - Sound the buzzer with the command of the printer profile
+/// Cut(CutMode) - Feed to the cutter and cut with the cutter of the printer profile
+/// Page(PageLayout) - Print the regions of the layout in page mode
+/// Wrapped(TextBlock) - Add Text wrapped to the columns of the active font and size
+/// Table(Table) - Add a table laid out on the columns of the active font and size
+/// Spacing(Spacing) - Set the line spacing, margins or position in dots or millimetres
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ESCPOSDataBuilder {
     Command(ESCPOSCommand),
     Text(String),
-    Image,
-    QrCode,
+    Image(ESCPOSImage),
+    QrCode(ESCPOSQRCode),
+    Barcode(Barcode),
+    Beep(Beep),
+    Cut(CutMode),
+    Page(PageLayout),
+    Wrapped(TextBlock),
+    Table(Table),
+    Spacing(Spacing),
 }
 
 /// Use this struct to build ESCPOS commands
 /// That implements ESCPOSBuilderTrait, which is a trait that has methods to add commands and convert to ESCPOS string
-/// e.g. 
+/// e.g.
 /// ```rust
-/// let mut builder = ESCPOSBuilder::new();
+/// # use eco_print::commands::command::*;
+/// let mut builder = ESCPOSBuilder::default();
 /// builder.add_command(ESCPOSDataBuilder::Command(ESCPOSCommand::LineFeed));
 /// builder.add_command(ESCPOSDataBuilder::Command(ESCPOSCommand::FontBold));
 /// builder.add_command(ESCPOSDataBuilder::Text("Hello World".to_string()));
-/// let cmd_escpos = builder.to_escpos();
+/// let cmd_escpos = builder.to_escpos_bytes();
+/// ```
+/// The commands that depend on the printer (buzzer, ...) are generated for the builder profile,
+/// ESCPOSBuilder::default() uses PrinterProfile::generic().
+///
+/// A job can start and/or end with ESC @ and the profile defaults (code page, line spacing, margin),
+/// so styles left by a previous job or another app don't leak into it, e.g.:
+/// ```rust
+/// # use eco_print::commands::command::*;
+/// let mut builder = ESCPOSBuilder::default();
+/// builder.set_initialize(true);
+/// builder.set_reset_at_end(true);
+/// builder.add_command(ESCPOSDataBuilder::Text("Hello World\n".to_string()));
+/// assert!(builder.to_escpos_bytes().starts_with(b"\x1B\x40"));
+/// ```
+///
+/// Text is encoded with the code page selected by the profile or the last CodePage command,
+/// the chars missing from it are printed as `?`, or the whole line as an image with the raster font when set.
+/// e.g.:
+/// ```rust
+/// # use eco_print::commands::command::*;
+/// let mut builder = ESCPOSBuilder::default();
+/// builder.add_command(ESCPOSDataBuilder::Command(ESCPOSCommand::CodePage(19)));
+/// builder.add_command(ESCPOSDataBuilder::Text("Café 5€\n".to_string()));
+/// assert!(builder.to_escpos_bytes().ends_with(b"Caf\x82 5\xD5\n"));
+/// ```
+/// Printers with Kanji mode print Chinese, Japanese or Korean with the double-byte encoding,
+/// the double-width characters take two columns when the text is wrapped, e.g.:
+/// ```rust
+/// # use eco_print::commands::command::*;
+/// # use eco_print::commands::codepage::TextEncoding;
+/// let mut builder = ESCPOSBuilder::default();
+/// builder.add_command(ESCPOSDataBuilder::Command(ESCPOSCommand::Encoding(TextEncoding::Big5)));
+/// builder.add_command(ESCPOSDataBuilder::Text("珍珠奶茶\n".to_string()));
+/// assert!(builder.to_escpos_bytes().starts_with(b"\x1C\x28\x43\x02\x00\x30\x01\x1C\x26\xAC\xC3"));
 /// ```
-#[derive(Default, Debug, Clone)]
-pub struct ESCPOSBuilder(Vec<ESCPOSDataBuilder>);
+/// Hebrew and Arabic are printed in visual order, right aligned unless another alignment is set,
+/// Arabic letters take the form of their position in the word (CP864 has most of the forms), e.g.:
+/// ```rust
+/// # use eco_print::commands::command::*;
+/// let mut builder = ESCPOSBuilder::default();
+/// builder.add_command(ESCPOSDataBuilder::Command(ESCPOSCommand::CodePage(36)));
+/// builder.add_command(ESCPOSDataBuilder::Text("שלום\n".to_string()));
+/// assert!(builder.to_escpos_bytes().ends_with(b"\x1B\x61\x02\x8D\x85\x8C\x99\n"));
+/// ```
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "JobJson", into = "JobJson")]
+pub struct ESCPOSBuilder {
+    commands: Vec<ESCPOSDataBuilder>,
+    profile: PrinterProfile,
+    initialize: bool,
+    reset_at_end: bool,
+    raster_font: Option<RasterFont>,
+}
+
+impl ESCPOSBuilder {
+    pub fn new(profile: PrinterProfile) -> Self {
+        Self {
+            commands: Vec::new(),
+            profile,
+            initialize: false,
+            reset_at_end: false,
+            raster_font: None,
+        }
+    }
+
+    pub fn commands(&self) -> &[ESCPOSDataBuilder] {
+        &self.commands
+    }
+
+    pub fn initialize(&self) -> bool {
+        self.initialize
+    }
+
+    pub fn reset_at_end(&self) -> bool {
+        self.reset_at_end
+    }
+
+    /// Start the job with ESC @ and the profile defaults
+    pub fn set_initialize(&mut self, initialize: bool) {
+        self.initialize = initialize;
+    }
+
+    /// End the job with ESC @ and the profile defaults.
+    /// ESC @ clears the print buffer, so the last text must end with a line feed to be printed.
+    pub fn set_reset_at_end(&mut self, reset_at_end: bool) {
+        self.reset_at_end = reset_at_end;
+    }
+
+    pub fn raster_font(&self) -> Option<&RasterFont> {
+        self.raster_font.as_ref()
+    }
+
+    /// Font used to print the lines with chars missing from the code page as images.
+    /// It's not part of the JSON job, set it again after reading a job.
+    pub fn set_raster_font(&mut self, raster_font: Option<RasterFont>) {
+        self.raster_font = raster_font;
+    }
+
+    /// ESC @ followed by the profile defaults that differ from the power-on state
+    fn reset_escpos_bytes(&self) -> Vec<u8> {
+        let mut cmds = vec![
+            ESCPOSCommand::Initialize,
+            ESCPOSCommand::CodePage(self.profile.code_page),
+        ];
+        if self.profile.encoding != TextEncoding::CodePage {
+            cmds.push(ESCPOSCommand::Encoding(self.profile.encoding));
+        }
+        if let Some(line_spacing) = self.profile.line_spacing {
+            cmds.push(ESCPOSCommand::LineSpacing(line_spacing));
+        }
+        if self.profile.left_margin_dots != 0 {
+            cmds.push(ESCPOSCommand::LeftMargin(self.profile.left_margin_dots));
+        }
+        cmds.iter().flat_map(|cmd| cmd.to_escpos_bytes()).collect()
+    }
+
+    pub fn profile(&self) -> &PrinterProfile {
+        &self.profile
+    }
+
+    pub fn set_profile(&mut self, profile: PrinterProfile) {
+        self.profile = profile;
+    }
+
+    /// Feed the last printed line past the cutter and cut, as supported by the profile.
+    /// Partial cuts fall back to full cuts on full-only cutters, printers without cutter only feed to the tear bar.
+    fn cut_escpos_bytes(&self, mode: CutMode) -> Vec<u8> {
+        let feed = self.profile.cutter_feed_dots;
+        let mode = match (self.profile.cutter, mode) {
+            (CutterType::None, _) => return ESCPOSCommand::FeedDots(feed).to_escpos_bytes(),
+            (CutterType::Full, CutMode::Partial) => CutMode::Full,
+            (_, mode) => mode,
+        };
+
+        let cut = match self.profile.cut_command {
+            CutCommand::FeedAndCut => {
+                return ESCPOSCommand::FeedAndCut { mode, feed }.to_escpos_bytes()
+            }
+            CutCommand::Cut if mode == CutMode::Full => ESCPOSCommand::Cut,
+            CutCommand::Cut => ESCPOSCommand::PartialCut,
+            CutCommand::Legacy => ESCPOSCommand::LegacyCut(mode),
+        };
+        let mut escpos = ESCPOSCommand::FeedDots(feed).to_escpos_bytes();
+        escpos.extend(cut.to_escpos_bytes());
+        escpos
+    }
+
+    /// Render the job for the profile, failing on the first entry the printer doesn't support.
+    /// to_escpos_bytes renders the same bytes but skips those entries with a warning.
+    /// e.g.:
+    /// ```rust
+    /// # use eco_print::commands::command::*;
+    /// # use eco_print::commands::page::PageLayout;
+    /// # use eco_print::profile::PrinterProfile;
+    /// let mut builder = ESCPOSBuilder::new(PrinterProfile::xprinter_xp58());
+    /// builder.add_command(ESCPOSDataBuilder::Page(PageLayout::new(384, 100)));
+    /// assert!(builder.render().is_err());
+    /// ```
+    pub fn render(&self) -> EcoPrintResult<Vec<u8>> {
+        let mut escpos = Vec::new();
+        if self.initialize {
+            escpos.extend(self.reset_escpos_bytes());
+        }
+        let mut state = TextState::new(&self.profile);
+        for cmd in &self.commands {
+            escpos.extend(self.data_escpos_bytes(cmd, &mut state)?);
+        }
+        if self.reset_at_end {
+            escpos.extend(self.reset_escpos_bytes());
+        }
+        Ok(escpos)
+    }
+
+    fn data_escpos_bytes(
+        &self,
+        cmd: &ESCPOSDataBuilder,
+        state: &mut TextState,
+    ) -> EcoPrintResult<Vec<u8>> {
+        let mut escpos = Vec::new();
+        // Images, codes and pages keep the alignment of the commands, not the one of the RTL text
+        let text = matches!(
+            cmd,
+            ESCPOSDataBuilder::Command(_)
+                | ESCPOSDataBuilder::Text(_)
+                | ESCPOSDataBuilder::Wrapped(_)
+                | ESCPOSDataBuilder::Table(_)
+                | ESCPOSDataBuilder::Spacing(_)
+        );
+        if state.rtl_aligned && !text {
+            escpos.extend(ESCPOSCommand::AlignLeft.to_escpos_bytes());
+            state.rtl_aligned = false;
+        }
+        escpos.extend(match cmd {
+            ESCPOSDataBuilder::Command(ESCPOSCommand::CodePage(n))
+                if !self.profile.supports_code_page(*n) =>
+            {
+                return Err(self.unsupported(format!("code page {}", n)));
+            }
+            ESCPOSDataBuilder::Command(cmd) => {
+                state.apply(cmd);
+                cmd.to_escpos_bytes()
+            }
+            ESCPOSDataBuilder::Text(str) => self.text_escpos_bytes(str, state)?,
+            ESCPOSDataBuilder::Wrapped(block) => {
+                let lines = block.to_lines(state.columns(&self.profile), state.align);
+                self.text_escpos_bytes(&lines.concat(), state)?
+            }
+            ESCPOSDataBuilder::Table(table) => {
+                let lines = table.to_lines(state.columns(&self.profile));
+                self.text_escpos_bytes(&lines.concat(), state)?
+            }
+            ESCPOSDataBuilder::Image(image) => self.image_escpos_bytes(image)?,
+            ESCPOSDataBuilder::QrCode(qrcode) if self.profile.qr_code => qrcode.to_escpos_bytes(),
+            ESCPOSDataBuilder::QrCode(qrcode) => self.image_escpos_bytes(&qrcode.to_image())?,
+            ESCPOSDataBuilder::Barcode(_) if !self.profile.barcode => {
+                return Err(self.unsupported("barcodes".to_string()));
+            }
+            ESCPOSDataBuilder::Barcode(barcode) => barcode.to_escpos_bytes(),
+            ESCPOSDataBuilder::Beep(beep) => beep.to_escpos_bytes(self.profile.buzzer),
+            ESCPOSDataBuilder::Cut(mode) => self.cut_escpos_bytes(*mode),
+            ESCPOSDataBuilder::Page(page) => self.page_escpos_bytes(page, state)?,
+            ESCPOSDataBuilder::Spacing(spacing) => {
+                let cmd = spacing.to_command(&self.profile);
+                state.apply(&cmd);
+                cmd.to_escpos_bytes()
+            }
+        });
+        Ok(escpos)
+    }
+
+    fn unsupported(&self, feature: String) -> EcoPrintError {
+        EcoPrintError::Unsupported(format!("{} on {}", feature, self.profile.name))
+    }
+
+    /// Text encoded with the code page of the state, line by line.
+    /// Right-to-left lines are put in visual order (Arabic is shaped) and, as left is the start
+    /// of the line, right aligned when the text is left aligned.
+    /// A line with chars missing from the code page is printed as an image with the raster font,
+    /// the image ends the line, so text sent before it on the same line is printed on its own.
+    fn text_escpos_bytes(&self, text: &str, state: &mut TextState) -> EcoPrintResult<Vec<u8>> {
+        let table = CharacterTable::from_encoding(state.encoding, state.kanji, state.code_page);
+        let mut escpos = Vec::new();
+        for line in text.split_inclusive('\n') {
+            let content = line.strip_suffix('\n').unwrap_or(line);
+            let (visual, rtl) = bidi::to_visual(content);
+            if state.align == Align::Left && !content.is_empty() && rtl != state.rtl_aligned {
+                let align = if rtl {
+                    ESCPOSCommand::AlignRight
+                } else {
+                    ESCPOSCommand::AlignLeft
+                };
+                escpos.extend(align.to_escpos_bytes());
+                state.rtl_aligned = rtl;
+            }
+            let line = format!("{}{}", visual, &line[content.len()..]);
+
+            match (table.encode(&line), &self.raster_font) {
+                (Ok(line_escpos), _) => escpos.extend(line_escpos),
+                (Err(_), Some(font)) => {
+                    let align = if state.rtl_aligned {
+                        Align::Right
+                    } else {
+                        state.align
+                    };
+                    let width = u32::from(state.line_width_dots);
+                    let image = font.render(&visual, width, align)?;
+                    escpos.extend(self.image_escpos_bytes(&image)?);
+                }
+                (Err(missing), None) => {
+                    log::warn!(
+                        "{:?} not on code page {}, printed as ?",
+                        missing,
+                        state.code_page
+                    );
+                    escpos.extend(table.encode_lossy(&line));
+                }
+            }
+        }
+        Ok(escpos)
+    }
+
+    /// GS v 0 raster, or ESC * bit image stripes when the profile has no raster support
+    fn image_escpos_bytes(&self, image: &ESCPOSImage) -> EcoPrintResult<Vec<u8>> {
+        if image.width() > u32::from(self.profile.print_width_dots) {
+            return Err(EcoPrintError::Image(format!(
+                "Image of {} dots is wider than the {} dots of {}",
+                image.width(),
+                self.profile.print_width_dots,
+                self.profile.name
+            )));
+        }
+        if self.profile.raster_image {
+            return Ok(image.to_raster_bytes());
+        }
+        let mut escpos = image.to_bit_image_bytes();
+        if let Some(line_spacing) = self.profile.line_spacing {
+            escpos.extend(ESCPOSCommand::LineSpacing(line_spacing).to_escpos_bytes());
+        }
+        Ok(escpos)
+    }
+
+    /// ESC L, then the area, direction and content of each region, and FF to print the page
+    fn page_escpos_bytes(&self, page: &PageLayout, state: &TextState) -> EcoPrintResult<Vec<u8>> {
+        if !self.profile.page_mode {
+            return Err(self.unsupported("page mode".to_string()));
+        }
+        if page.width() > self.profile.print_width_dots {
+            return Err(EcoPrintError::Layout(format!(
+                "Page of {} dots is wider than the {} dots of {}",
+                page.width(),
+                self.profile.print_width_dots,
+                self.profile.name
+            )));
+        }
+        let mut escpos = ESCPOSCommand::PageModeOn.to_escpos_bytes();
+        for region in page.regions() {
+            let area = ESCPOSCommand::PrintArea {
+                x: region.x,
+                y: region.y,
+                width: region.width,
+                height: region.height,
+            };
+            escpos.extend(area.to_escpos_bytes());
+            escpos.extend(ESCPOSCommand::PrintDirection(region.direction).to_escpos_bytes());
+            // Text is wrapped to the area, along the print direction
+            let area_width = match region.direction {
+                PrintDirection::LeftToRight | PrintDirection::RightToLeft => region.width,
+                PrintDirection::BottomToTop | PrintDirection::TopToBottom => region.height,
+            };
+            let mut region_state = TextState {
+                line_width_dots: area_width,
+                print_width_dots: area_width,
+                left_margin_dots: 0,
+                print_area_width_dots: None,
+                ..*state
+            };
+            for cmd in &region.content {
+                match cmd {
+                    ESCPOSDataBuilder::Page(_) | ESCPOSDataBuilder::Cut(_) => {
+                        log::warn!("{:?} is not allowed inside a page region, skipped", cmd)
+                    }
+                    cmd => escpos.extend(self.data_escpos_bytes(cmd, &mut region_state)?),
+                }
+            }
+        }
+        escpos.extend(ESCPOSCommand::PrintPage.to_escpos_bytes());
+        Ok(escpos)
+    }
+}
 
 impl ESCPOSBuilderTrait for ESCPOSBuilder {
     fn add_command(&mut self, _cmd: ESCPOSDataBuilder) {
-        self.0.push(_cmd);
+        self.commands.push(_cmd);
     }
     fn add_commands(&mut self, _cmds: Vec<ESCPOSDataBuilder>) {
-        self.0.extend(_cmds);
+        self.commands.extend(_cmds);
     }
-    fn to_escpos(&self) -> String {
-        let mut escpos = String::new();
-        for cmd in &self.0 {
-            match cmd {
-                ESCPOSDataBuilder::Command(cmd) => {
-                    escpos.push_str(cmd.to_escpos().as_str());
-                },
-                ESCPOSDataBuilder::Text(str) => {
-                    escpos.push_str(str);
-                },
-                ESCPOSDataBuilder::Image => todo!(),
-                ESCPOSDataBuilder::QrCode => todo!(),
+    fn to_escpos_bytes(&self) -> Vec<u8> {
+        let mut escpos = Vec::new();
+        if self.initialize {
+            escpos.extend(self.reset_escpos_bytes());
+        }
+        let mut state = TextState::new(&self.profile);
+        for cmd in &self.commands {
+            match self.data_escpos_bytes(cmd, &mut state) {
+                Ok(cmd_escpos) => escpos.extend(cmd_escpos),
+                Err(_err) => log::warn!("{:?} skipped - {}", cmd, _err),
             }
         }
+        if self.reset_at_end {
+            escpos.extend(self.reset_escpos_bytes());
+        }
         escpos
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,180 @@
+use std::{fmt, path::Path};
+
+use ab_glyph::{point, Font, FontArc, GlyphId, PxScale, ScaleFont};
+
+use crate::{EcoPrintError, EcoPrintResult};
+
+use super::{image::ESCPOSImage, text::Align};
+
+/// TrueType/OpenType font used to print text as a raster image,
+/// for the scripts and symbols no code page of the printer has (CJK, Thai, emoji, ...).
+/// Dots covered by at least half by a glyph are printed.
+/// e.g.:
+/// ```rust,no_run
+/// use eco_print::commands::command::*;
+/// use eco_print::commands::font::RasterFont;
+/// use eco_print::commands::text::Align;
+///
+/// let font = RasterFont::from_path("assets/fonts/NotoSansSC-Regular.otf", 24.0).unwrap();
+/// let image = font.render("宫保鸡丁 x2", 384, Align::Left).unwrap();
+///
+/// // Or let the builder print the lines with chars missing from the code page as images
+/// let mut builder = ESCPOSBuilder::default();
+/// builder.set_raster_font(Some(font));
+/// builder.add_command(ESCPOSDataBuilder::Text("宫保鸡丁 x2\n".to_string()));
+/// ```
+#[derive(Clone)]
+pub struct RasterFont {
+    font: FontArc,
+    size: f32,
+}
+
+impl fmt::Debug for RasterFont {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RasterFont")
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+impl RasterFont {
+    /// Font from the content of a TTF/OTF file, `size` is the height of the glyphs in dots
+    pub fn new(data: Vec<u8>, size: f32) -> EcoPrintResult<Self> {
+        let font = FontArc::try_from_vec(data)
+            .map_err(|_err| EcoPrintError::Font(format!("Invalid font - {}", _err)))?;
+        Ok(Self {
+            font,
+            size: size.max(1.0),
+        })
+    }
+
+    pub fn from_path(path: impl AsRef<Path>, size: f32) -> EcoPrintResult<Self> {
+        let data = std::fs::read(path.as_ref()).map_err(|_err| {
+            EcoPrintError::Font(format!("Error opening {:?} - {}", path.as_ref(), _err))
+        })?;
+        Self::new(data, size)
+    }
+
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size.max(1.0);
+        self
+    }
+
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+
+    /// Check if the font has a glyph for the char
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.font.glyph_id(c) != GlyphId(0)
+    }
+
+    /// Height of a line in dots
+    pub fn line_height(&self) -> u32 {
+        let font = self.font.as_scaled(PxScale::from(self.size));
+        (font.ascent() - font.descent()).ceil().max(1.0) as u32
+    }
+
+    /// Width of the text on a single line in dots
+    pub fn text_width(&self, text: &str) -> f32 {
+        let font = self.font.as_scaled(PxScale::from(self.size));
+        let mut width = 0.0;
+        let mut previous: Option<GlyphId> = None;
+        for c in text.chars() {
+            let id = font.glyph_id(c);
+            if let Some(previous) = previous {
+                width += font.kern(previous, id);
+            }
+            width += font.h_advance(id);
+            previous = Some(id);
+        }
+        width
+    }
+
+    /// Lay out the text on lines of `width` dots, wrapped on spaces (or between chars
+    /// when a word doesn't fit on a line) and aligned, as an image `width` dots wide
+    pub fn render(&self, text: &str, width: u32, align: Align) -> EcoPrintResult<ESCPOSImage> {
+        if width == 0 {
+            return Err(EcoPrintError::Font("Empty line width".to_string()));
+        }
+        let lines: Vec<String> = text
+            .lines()
+            .flat_map(|paragraph| self.wrap(paragraph, width as f32))
+            .collect();
+        let font = self.font.as_scaled(PxScale::from(self.size));
+        let line_height = self.line_height();
+        let height = line_height * lines.len().max(1) as u32;
+        let width_bytes = width.div_ceil(8) as usize;
+        let mut data = vec![0u8; width_bytes * height as usize];
+
+        for (i, line) in lines.iter().enumerate() {
+            let free = (width as f32 - self.text_width(line)).max(0.0);
+            let mut x = match align {
+                Align::Left => 0.0,
+                Align::Center => (free / 2.0).floor(),
+                Align::Right => free,
+            };
+            let baseline = (i as u32 * line_height) as f32 + font.ascent().ceil();
+            let mut previous: Option<GlyphId> = None;
+            for c in line.chars() {
+                let id = font.glyph_id(c);
+                if let Some(previous) = previous {
+                    x += font.kern(previous, id);
+                }
+                let glyph = id.with_scale_and_position(self.size, point(x, baseline));
+                x += font.h_advance(id);
+                previous = Some(id);
+
+                let Some(outline) = self.font.outline_glyph(glyph) else {
+                    continue;
+                };
+                let bounds = outline.px_bounds();
+                outline.draw(|gx, gy, coverage| {
+                    let dot_x = bounds.min.x as i32 + gx as i32;
+                    let dot_y = bounds.min.y as i32 + gy as i32;
+                    if coverage < 0.5
+                        || dot_x < 0
+                        || dot_y < 0
+                        || dot_x >= width as i32
+                        || dot_y >= height as i32
+                    {
+                        return;
+                    }
+                    let (dot_x, dot_y) = (dot_x as usize, dot_y as usize);
+                    data[dot_y * width_bytes + dot_x / 8] |= 0x80 >> (dot_x % 8);
+                });
+            }
+        }
+        ESCPOSImage::from_raster(width, height, data)
+    }
+
+    /// Lines of at most `width` dots
+    fn wrap(&self, paragraph: &str, width: f32) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if self.text_width(&candidate) <= width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            // Break the word between chars, scripts like CJK and Thai don't use spaces
+            for c in word.chars() {
+                line.push(c);
+                if line.chars().count() > 1 && self.text_width(&line) > width {
+                    line.pop();
+                    lines.push(std::mem::replace(&mut line, c.to_string()));
+                }
+            }
+        }
+        lines.push(line);
+        lines
+    }
+}
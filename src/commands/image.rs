@@ -1 +1,138 @@
-// TODO
\ No newline at end of file
+use std::path::Path;
+
+use image::DynamicImage;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{json::ImageJson, EcoPrintError, EcoPrintResult};
+
+/// Monochrome image ready to be sent to the printer.
+/// Pixels darker than the middle gray are printed, transparent pixels are left blank.
+/// e.g.:
+/// ```rust,no_run
+/// use eco_print::commands::image::ESCPOSImage;
+///
+/// let logo = ESCPOSImage::from_path("assets/imgs/square.png").unwrap();
+/// let raster = logo.to_raster_bytes();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "ImageJson", into = "ImageJson")]
+pub struct ESCPOSImage {
+    width: u32,
+    height: u32,
+    /// Rows of width_bytes() bytes, MSB is the leftmost dot, 1 is black
+    data: Vec<u8>,
+}
+
+impl ESCPOSImage {
+    pub fn from_path(path: impl AsRef<Path>) -> EcoPrintResult<Self> {
+        let image = image::open(path.as_ref()).map_err(|_err| {
+            EcoPrintError::Image(format!("Error opening {:?} - {}", path.as_ref(), _err))
+        })?;
+        Ok(Self::from_image(&image))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> EcoPrintResult<Self> {
+        let image = image::load_from_memory(bytes)
+            .map_err(|_err| EcoPrintError::Image(format!("Error decoding image - {}", _err)))?;
+        Ok(Self::from_image(&image))
+    }
+
+    pub fn from_image(image: &DynamicImage) -> Self {
+        let image = image.to_luma_alpha8();
+        let (width, height) = image.dimensions();
+        let width_bytes = width.div_ceil(8) as usize;
+        let mut data = vec![0u8; width_bytes * height as usize];
+
+        for (x, y, pixel) in image.enumerate_pixels() {
+            let [luma, alpha] = pixel.0;
+            if alpha >= 128 && luma < 128 {
+                data[y as usize * width_bytes + x as usize / 8] |= 0x80 >> (x % 8);
+            }
+        }
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Build the image from packed rows (MSB leftmost, 1 is black)
+    pub fn from_raster(width: u32, height: u32, data: Vec<u8>) -> EcoPrintResult<Self> {
+        if data.len() != width.div_ceil(8) as usize * height as usize {
+            return Err(EcoPrintError::Image(format!(
+                "Raster of {} bytes doesn't match {}x{}",
+                data.len(),
+                width,
+                height
+            )));
+        }
+        Ok(Self {
+            width,
+            height,
+            data,
+        })
+    }
+
+    /// Width in dots
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in dots
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn width_bytes(&self) -> u32 {
+        self.width.div_ceil(8)
+    }
+
+    /// Packed rows, MSB is the leftmost dot, 1 is black
+    pub fn raster(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Check if the dot is black
+    pub fn is_black(&self, x: u32, y: u32) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        let byte = self.data[(y * self.width_bytes() + x / 8) as usize];
+        byte & (0x80 >> (x % 8)) != 0
+    }
+
+    /// GS v 0 - Print the image as a raster bit image
+    pub fn to_raster_bytes(&self) -> Vec<u8> {
+        let [xl, xh, ..] = self.width_bytes().to_le_bytes();
+        let [yl, yh, ..] = self.height.to_le_bytes();
+        let mut escpos = vec![0x1D, 0x76, 0x30, 0x00, xl, xh, yl, yh];
+        escpos.extend(&self.data);
+        escpos
+    }
+
+    /// ESC * 33 - Print the image as 24-dot bit image stripes, for printers without GS v 0.
+    /// Leaves the line spacing at the default (ESC 2).
+    pub fn to_bit_image_bytes(&self) -> Vec<u8> {
+        let [nl, nh, ..] = self.width.to_le_bytes();
+        // Stripes must touch each other, so the line feed is 24 dots
+        let mut escpos = vec![0x1B, 0x33, 24];
+        for stripe in 0..self.height.div_ceil(24) {
+            escpos.extend([0x1B, 0x2A, 33, nl, nh]);
+            for x in 0..self.width {
+                for y_byte in 0..3 {
+                    let mut byte = 0u8;
+                    for bit in 0..8 {
+                        if self.is_black(x, stripe * 24 + y_byte * 8 + bit) {
+                            byte |= 0x80 >> bit;
+                        }
+                    }
+                    escpos.push(byte);
+                }
+            }
+            escpos.push(b'\n');
+        }
+        escpos.extend([0x1B, 0x32]);
+        escpos
+    }
+}
@@ -1,3 +1,12 @@
+pub mod barcode;
+pub mod bidi;
+pub mod buzzer;
+pub mod codepage;
 pub mod command;
+pub mod font;
 pub mod image;
+pub mod page;
 pub mod qrcode;
+pub mod spacing;
+pub mod table;
+pub mod text;
@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{EcoPrintError, EcoPrintResult};
+
+use super::command::ESCPOSDataBuilder;
+
+/// ESC T n - Direction and starting corner of the text in a page mode area
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrintDirection {
+    /// Starts at the upper left, no rotation
+    #[default]
+    LeftToRight = 0,
+    /// Starts at the lower left, rotated 90° counterclockwise
+    BottomToTop = 1,
+    /// Starts at the lower right, upside down
+    RightToLeft = 2,
+    /// Starts at the upper right, rotated 90° clockwise
+    TopToBottom = 3,
+}
+
+/// Block of content printed in its own area of the page, coordinates in dots from the upper left corner
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PageRegion {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+    pub direction: PrintDirection,
+    pub content: Vec<ESCPOSDataBuilder>,
+}
+
+impl PageRegion {
+    pub fn new(x: u16, y: u16, width: u16, height: u16) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_direction(mut self, direction: PrintDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    pub fn add_command(&mut self, cmd: ESCPOSDataBuilder) {
+        self.content.push(cmd);
+    }
+
+    pub fn add_commands(&mut self, cmds: Vec<ESCPOSDataBuilder>) {
+        self.content.extend(cmds);
+    }
+}
+
+/// Page printed in page mode: the regions are laid out in the page buffer and printed at once,
+/// so text can be placed next to an image or rotated.
+/// e.g.:
+/// ```rust
+/// use eco_print::commands::command::*;
+/// use eco_print::commands::page::*;
+///
+/// let mut page = PageLayout::new(384, 200);
+/// let mut title = PageRegion::new(0, 0, 384, 40);
+/// title.add_command(ESCPOSDataBuilder::Text("Shipping label".to_string()));
+/// page.add_region(title).unwrap();
+///
+/// let mut side = PageRegion::new(344, 40, 40, 160).with_direction(PrintDirection::TopToBottom);
+/// side.add_command(ESCPOSDataBuilder::Text("FRAGILE".to_string()));
+/// page.add_region(side).unwrap();
+///
+/// let mut builder = ESCPOSBuilder::default();
+/// builder.add_command(ESCPOSDataBuilder::Page(page));
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageLayout {
+    width: u16,
+    height: u16,
+    regions: Vec<PageRegion>,
+}
+
+impl PageLayout {
+    /// Page of width x height dots
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            regions: Vec::new(),
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn regions(&self) -> &[PageRegion] {
+        &self.regions
+    }
+
+    /// Add a region, it must be inside the page and not empty
+    pub fn add_region(&mut self, region: PageRegion) -> EcoPrintResult<()> {
+        let inside = u32::from(region.x) + u32::from(region.width) <= u32::from(self.width)
+            && u32::from(region.y) + u32::from(region.height) <= u32::from(self.height);
+        if region.width == 0 || region.height == 0 || !inside {
+            return Err(EcoPrintError::Layout(format!(
+                "Region {}x{} at ({}, {}) doesn't fit in the {}x{} page",
+                region.width, region.height, region.x, region.y, self.width, self.height
+            )));
+        }
+        self.regions.push(region);
+        Ok(())
+    }
+}
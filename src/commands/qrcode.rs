@@ -1,16 +1,119 @@
-use std::error::Error;
+use std::fmt;
 
-use qrcode::QrCode;
+pub use qrcode::EcLevel;
+use qrcode::{Color, QrCode};
 
+use serde::{Deserialize, Serialize};
+
+use crate::{json::QrCodeJson, EcoPrintError, EcoPrintResult};
+
+use super::image::ESCPOSImage;
+
+/// QR code printed with GS ( k (model 2), or as a raster image on printers without QR support.
+/// e.g.:
+/// ```rust
+/// use eco_print::commands::command::*;
+/// use eco_print::commands::qrcode::{ESCPOSQRCode, EcLevel};
+///
+/// let qrcode = ESCPOSQRCode::new("https://example.com/r/1234".to_string())
+///     .unwrap()
+///     .with_module_size(6)
+///     .with_error_correction(EcLevel::Q)
+///     .unwrap();
+/// let mut builder = ESCPOSBuilder::default();
+/// builder.add_command(ESCPOSDataBuilder::QrCode(qrcode));
+/// ```
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "QrCodeJson", into = "QrCodeJson")]
 pub struct ESCPOSQRCode {
     pub qrcode: QrCode,
+    data: String,
+    module_size: u8,
+}
+
+impl fmt::Debug for ESCPOSQRCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ESCPOSQRCode")
+            .field("data", &self.data)
+            .field("module_size", &self.module_size)
+            .field("error_correction", &self.qrcode.error_correction_level())
+            .finish()
+    }
 }
 
 impl ESCPOSQRCode {
-    pub fn new(data: String) -> Result<Self, Box<dyn Error>> {
-        let qrcode = QrCode::new(data)?;
-        Ok(Self { qrcode })
+    /// QR code with medium error correction and modules of 4 dots
+    pub fn new(data: String) -> EcoPrintResult<Self> {
+        let qrcode = QrCode::new(&data).map_err(|_err| {
+            EcoPrintError::QrCode(format!("Error encoding {:?} - {}", data, _err))
+        })?;
+        Ok(Self {
+            qrcode,
+            data,
+            module_size: 4,
+        })
+    }
+
+    pub fn with_error_correction(mut self, level: EcLevel) -> EcoPrintResult<Self> {
+        self.qrcode = QrCode::with_error_correction_level(&self.data, level).map_err(|_err| {
+            EcoPrintError::QrCode(format!("Error encoding {:?} - {}", self.data, _err))
+        })?;
+        Ok(self)
+    }
+
+    /// Size of a module in dots, 1 to 16
+    pub fn with_module_size(mut self, module_size: u8) -> Self {
+        self.module_size = module_size.clamp(1, 16);
+        self
+    }
+
+    pub fn data(&self) -> &str {
+        &self.data
+    }
+
+    pub fn module_size(&self) -> u8 {
+        self.module_size
     }
 
-    
+    /// GS ( k - Select model 2, set the module size and error correction, store the data and print it
+    pub fn to_escpos_bytes(&self) -> Vec<u8> {
+        let ec = match self.qrcode.error_correction_level() {
+            EcLevel::L => 48,
+            EcLevel::M => 49,
+            EcLevel::Q => 50,
+            EcLevel::H => 51,
+        };
+        // Model 2, module size and error correction level
+        let mut escpos = vec![0x1D, 0x28, 0x6B, 0x04, 0x00, 0x31, 0x41, 0x32, 0x00];
+        escpos.extend([0x1D, 0x28, 0x6B, 0x03, 0x00, 0x31, 0x43, self.module_size]);
+        escpos.extend([0x1D, 0x28, 0x6B, 0x03, 0x00, 0x31, 0x45, ec]);
+        let [pl, ph, ..] = (self.data.len() as u32 + 3).to_le_bytes();
+        escpos.extend([0x1D, 0x28, 0x6B, pl, ph, 0x31, 0x50, 0x30]);
+        escpos.extend(self.data.as_bytes());
+        escpos.extend([0x1D, 0x28, 0x6B, 0x03, 0x00, 0x31, 0x51, 0x30]);
+        escpos
+    }
+
+    /// Image of the QR code with the module size and a quiet zone of 4 modules
+    pub fn to_image(&self) -> ESCPOSImage {
+        let modules = self.qrcode.width() as u32;
+        let colors = self.qrcode.to_colors();
+        let scale = u32::from(self.module_size);
+        let size = (modules + 8) * scale;
+        let width_bytes = size.div_ceil(8);
+        let mut data = vec![0u8; (width_bytes * size) as usize];
+
+        for y in 0..size {
+            for x in 0..size {
+                let (mx, my) = ((x / scale).wrapping_sub(4), (y / scale).wrapping_sub(4));
+                if mx < modules
+                    && my < modules
+                    && colors[(my * modules + mx) as usize] == Color::Dark
+                {
+                    data[(y * width_bytes + x / 8) as usize] |= 0x80 >> (x % 8);
+                }
+            }
+        }
+        ESCPOSImage::from_raster(size, size, data).expect("raster size matches the QR code size")
+    }
 }
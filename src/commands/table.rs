@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+
+use super::text::{display_width, truncate, wrap, Align};
+
+/// Width of a table column in columns, wide characters (CJK, ...) take two columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnWidth {
+    Fixed(usize),
+    /// Percentage of the line, gaps excluded
+    Percent(u8),
+    /// Width of the longest cell, the first auto column takes the columns left
+    Auto,
+}
+
+/// What to do with the cells longer than their column
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Overflow {
+    #[default]
+    Truncate,
+    Wrap,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableColumn {
+    pub width: ColumnWidth,
+    pub align: Align,
+    /// Character used to pad the cells, e.g.: '.' for "Coffee ...... 3.50"
+    pub fill: char,
+}
+
+impl TableColumn {
+    pub fn new(width: ColumnWidth) -> Self {
+        Self {
+            width,
+            align: Align::Left,
+            fill: ' ',
+        }
+    }
+
+    pub fn with_align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn with_fill(mut self, fill: char) -> Self {
+        self.fill = fill;
+        self
+    }
+}
+
+/// Table laid out on the columns per line of the active font, so it fits 58mm and 80mm paper alike.
+/// e.g.:
+/// ```rust
+/// use eco_print::commands::command::*;
+/// use eco_print::commands::table::*;
+/// use eco_print::commands::text::Align;
+///
+/// let mut table = Table::new(vec![
+///     TableColumn::new(ColumnWidth::Auto).with_fill('.'),
+///     TableColumn::new(ColumnWidth::Fixed(8)).with_align(Align::Right),
+/// ]);
+/// table.add_row(vec!["Coffee", "3.50"]);
+/// table.add_row(vec!["Croissant", "2.10"]);
+/// assert_eq!(table.to_lines(24)[0], "Coffee.........     3.50\n");
+///
+/// // Widths wider than the line are shrunk to fit it
+/// let wide = Table::new(vec![
+///     TableColumn::new(ColumnWidth::Percent(80)),
+///     TableColumn::new(ColumnWidth::Percent(80)),
+///     TableColumn::new(ColumnWidth::Fixed(40)),
+/// ]);
+/// assert_eq!(wide.column_widths(32), vec![9, 9, 11]);
+///
+/// let mut builder = ESCPOSBuilder::default();
+/// builder.add_command(ESCPOSDataBuilder::Table(table));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Table {
+    columns: Vec<TableColumn>,
+    rows: Vec<Vec<String>>,
+    overflow: Overflow,
+    gap: usize,
+}
+
+impl Table {
+    pub fn new(columns: Vec<TableColumn>) -> Self {
+        Self {
+            columns,
+            rows: Vec::new(),
+            overflow: Overflow::Truncate,
+            gap: 1,
+        }
+    }
+
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Spaces between the columns, 1 by default
+    pub fn with_gap(mut self, gap: usize) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Add a row, missing cells are left empty and extra cells are ignored
+    pub fn add_row<S: Into<String>>(&mut self, cells: Vec<S>) {
+        self.rows.push(cells.into_iter().map(Into::into).collect());
+    }
+
+    pub fn columns(&self) -> &[TableColumn] {
+        &self.columns
+    }
+
+    pub fn rows(&self) -> &[Vec<String>] {
+        &self.rows
+    }
+
+    /// Width of each column on a line of `line_columns` characters
+    pub fn column_widths(&self, line_columns: usize) -> Vec<usize> {
+        let gaps = self.gap * self.columns.len().saturating_sub(1);
+        let available = line_columns.saturating_sub(gaps);
+
+        let mut widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| match column.width {
+                ColumnWidth::Fixed(width) => width.min(available),
+                ColumnWidth::Percent(percent) => available * usize::from(percent.min(100)) / 100,
+                ColumnWidth::Auto => self
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.get(i))
+                    .map(|cell| display_width(cell))
+                    .max()
+                    .unwrap_or(0),
+            })
+            .collect();
+
+        let (auto, sized): (Vec<usize>, Vec<usize>) =
+            (0..self.columns.len()).partition(|i| self.columns[*i].width == ColumnWidth::Auto);
+        let fixed: usize = sized.iter().map(|i| widths[*i]).sum();
+        if fixed > available {
+            // Shrink the fixed and percent columns in proportion to their width, so the rows fit the line
+            for i in &sized {
+                widths[*i] = widths[*i] * available / fixed;
+            }
+        }
+        let fixed: usize = sized.iter().map(|i| widths[*i]).sum();
+        let remaining = available - fixed;
+        let wanted: usize = auto.iter().map(|i| widths[*i]).sum();
+
+        if wanted > remaining {
+            // Shrink the auto columns in proportion to their content, keeping a column each when there is room
+            let min = usize::from(remaining >= auto.len());
+            for i in &auto {
+                widths[*i] = (widths[*i] * remaining / wanted).max(min);
+            }
+            while auto.iter().map(|i| widths[*i]).sum::<usize>() > remaining {
+                if let Some(widest) = auto.iter().copied().max_by_key(|i| widths[*i]) {
+                    widths[widest] -= 1;
+                }
+            }
+        } else if let Some(first) = auto.first() {
+            widths[*first] += remaining - wanted;
+        }
+        widths
+    }
+
+    /// Lines of the table, ending with a line feed
+    pub fn to_lines(&self, line_columns: usize) -> Vec<String> {
+        let widths = self.column_widths(line_columns);
+        let separator = " ".repeat(self.gap);
+        let mut lines = Vec::new();
+
+        for row in &self.rows {
+            let cells: Vec<Vec<String>> = widths
+                .iter()
+                .enumerate()
+                .map(|(i, width)| {
+                    let cell = row.get(i).map(String::as_str).unwrap_or("");
+                    match self.overflow {
+                        _ if *width == 0 => vec![String::new()],
+                        Overflow::Truncate => vec![truncate(cell, *width).to_string()],
+                        Overflow::Wrap => {
+                            // Keep the indentation of the cell on every line
+                            let text = cell.trim_start();
+                            let indent = (cell.len() - text.len()).min(width - 1);
+                            wrap(text, width - indent, 0)
+                                .into_iter()
+                                .map(|line| format!("{}{}", " ".repeat(indent), line))
+                                .collect()
+                        }
+                    }
+                })
+                .collect();
+            let height = cells.iter().map(Vec::len).max().unwrap_or(1);
+
+            for line in 0..height {
+                let padded: Vec<String> = cells
+                    .iter()
+                    .zip(&self.columns)
+                    .zip(&widths)
+                    .map(|((cell, column), width)| match cell.get(line) {
+                        Some(text) => pad(text, *width, column.align, column.fill),
+                        None => " ".repeat(*width),
+                    })
+                    .collect();
+                lines.push(format!("{}\n", padded.join(&separator)));
+            }
+        }
+        lines
+    }
+}
+
+/// Pad the text to `width` columns with the fill character
+fn pad(text: &str, width: usize, align: Align, fill: char) -> String {
+    let missing = width.saturating_sub(display_width(text));
+    let (left, right) = match align {
+        Align::Left => (0, missing),
+        Align::Center => (missing / 2, missing - missing / 2),
+        Align::Right => (missing, 0),
+    };
+    let fill = |count: usize| std::iter::repeat_n(fill, count).collect::<String>();
+    format!("{}{}{}", fill(left), text, fill(right))
+}
@@ -0,0 +1,287 @@
+use serde::{Deserialize, Serialize};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::profile::PrinterProfile;
+
+use super::{codepage::TextEncoding, command::ESCPOSCommand};
+
+/// Character font, the columns per line of each font come from the printer profile
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Font {
+    #[default]
+    A,
+    B,
+    C,
+}
+
+/// ESC a - Text justification
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Text wrapped on word boundaries to the columns left by the active font, size and alignment.
+/// The lines after the first one are indented by `hanging_indent` columns when the text is left aligned.
+/// e.g.:
+/// ```rust
+/// use eco_print::commands::command::*;
+/// use eco_print::commands::text::TextBlock;
+///
+/// let mut builder = ESCPOSBuilder::default();
+/// builder.add_command(ESCPOSDataBuilder::Wrapped(
+///     TextBlock::new("2x Organic whole wheat sourdough bread, sliced").with_hanging_indent(3),
+/// ));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextBlock {
+    pub text: String,
+    pub hanging_indent: usize,
+}
+
+impl TextBlock {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            hanging_indent: 0,
+        }
+    }
+
+    pub fn with_hanging_indent(mut self, hanging_indent: usize) -> Self {
+        self.hanging_indent = hanging_indent;
+        self
+    }
+
+    /// Lines of at most `columns` columns, ending with a line feed
+    pub fn to_lines(&self, columns: usize, align: Align) -> Vec<String> {
+        let indent = match align {
+            Align::Left if self.hanging_indent < columns => self.hanging_indent,
+            _ => 0,
+        };
+        let mut lines = Vec::new();
+        for paragraph in self.text.lines() {
+            for (i, line) in wrap(paragraph, columns, indent).into_iter().enumerate() {
+                let indent = if i == 0 { 0 } else { indent };
+                lines.push(format!("{}{}\n", " ".repeat(indent), line));
+            }
+        }
+        lines
+    }
+}
+
+/// Columns taken by the text when printed, CJK and other wide characters take two columns
+/// e.g.:
+/// ```rust
+/// use eco_print::commands::text::display_width;
+///
+/// assert_eq!(display_width("Latte"), 5);
+/// assert_eq!(display_width("拿铁"), 4);
+/// ```
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+/// Longest start of the text that fits on `columns` columns
+pub fn truncate(text: &str, columns: usize) -> &str {
+    let mut width = 0;
+    for (i, c) in text.char_indices() {
+        width += c.width().unwrap_or(0);
+        if width > columns {
+            return &text[..i];
+        }
+    }
+    text
+}
+
+/// Wrap the text on spaces, the lines after the first one are `indent` columns shorter.
+/// Words longer than a line are broken.
+/// e.g.:
+/// ```rust
+/// use eco_print::commands::text::wrap;
+///
+/// assert_eq!(wrap("Espresso macchiato", 10, 0), vec!["Espresso", "macchiato"]);
+/// assert_eq!(wrap("Supercalifragilistic", 8, 0), vec!["Supercal", "ifragili", "stic"]);
+/// assert_eq!(wrap("宫保鸡丁", 5, 0), vec!["宫保", "鸡丁"]);
+/// ```
+pub fn wrap(text: &str, columns: usize, indent: usize) -> Vec<String> {
+    let columns = columns.max(1);
+    let continuation = columns - indent.min(columns - 1);
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    let mut line_len = 0;
+
+    for word in text.split_whitespace() {
+        let word_len = display_width(word);
+        let width = if lines.is_empty() {
+            columns
+        } else {
+            continuation
+        };
+        if line_len > 0 && line_len + 1 + word_len <= width {
+            line.push(' ');
+            line.push_str(word);
+            line_len += 1 + word_len;
+            continue;
+        }
+        if line_len > 0 {
+            lines.push(std::mem::take(&mut line));
+        }
+
+        // Break the word when it doesn't fit on a line on its own
+        let mut rest = word;
+        loop {
+            let width = if lines.is_empty() {
+                columns
+            } else {
+                continuation
+            };
+            let mut part = truncate(rest, width);
+            if part.is_empty() {
+                // A wide character on a single column line
+                part = &rest[..rest.chars().next().map_or(0, char::len_utf8)];
+            }
+            rest = &rest[part.len()..];
+            line = part.to_string();
+            line_len = display_width(part);
+            if rest.is_empty() {
+                break;
+            }
+            lines.push(std::mem::take(&mut line));
+        }
+    }
+    if line_len > 0 || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Font, size, alignment, margins and encoding set by the commands rendered so far,
+/// to know how many columns fit on a line and how to encode the text
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TextState {
+    pub font: Font,
+    pub width_multiplier: u8,
+    pub align: Align,
+    /// Width of a line in dots, the print area or the page mode area width
+    pub line_width_dots: u16,
+    /// Printable width the margin and the print area width are taken from
+    pub print_width_dots: u16,
+    /// GS L left margin
+    pub left_margin_dots: u16,
+    /// GS W print area width, None is up to the right edge
+    pub print_area_width_dots: Option<u16>,
+    /// ESC SP space on the right of each character
+    pub character_spacing: u8,
+    /// ESC t table the text is encoded with
+    pub code_page: u8,
+    pub encoding: TextEncoding,
+    /// FS & Kanji mode, the double-byte encodings are only used in Kanji mode
+    pub kanji: bool,
+    /// The printer is right aligned for right-to-left text while the text is left aligned
+    pub rtl_aligned: bool,
+}
+
+impl TextState {
+    pub fn new(profile: &PrinterProfile) -> Self {
+        Self {
+            font: Font::A,
+            width_multiplier: 1,
+            align: Align::Left,
+            line_width_dots: profile
+                .print_width_dots
+                .saturating_sub(profile.left_margin_dots),
+            print_width_dots: profile.print_width_dots,
+            left_margin_dots: profile.left_margin_dots,
+            print_area_width_dots: None,
+            character_spacing: 0,
+            code_page: profile.code_page,
+            encoding: profile.encoding,
+            kanji: profile.encoding.double_byte().is_some(),
+            rtl_aligned: false,
+        }
+    }
+
+    pub fn apply(&mut self, cmd: &ESCPOSCommand) {
+        if matches!(
+            cmd,
+            ESCPOSCommand::AlignLeft
+                | ESCPOSCommand::AlignCenter
+                | ESCPOSCommand::AlignRight
+                | ESCPOSCommand::Initialize
+        ) {
+            self.rtl_aligned = false;
+        }
+        match cmd {
+            ESCPOSCommand::FontA => self.font = Font::A,
+            ESCPOSCommand::FontB => self.font = Font::B,
+            ESCPOSCommand::FontC => self.font = Font::C,
+            ESCPOSCommand::AlignLeft => self.align = Align::Left,
+            ESCPOSCommand::AlignCenter => self.align = Align::Center,
+            ESCPOSCommand::AlignRight => self.align = Align::Right,
+            // ESC ! sets the font and the double width together
+            ESCPOSCommand::DoubleWidthOn => {
+                self.font = Font::A;
+                self.width_multiplier = 2;
+            }
+            ESCPOSCommand::DoubleWidthOff
+            | ESCPOSCommand::DoubleHeightOn
+            | ESCPOSCommand::DoubleHeightOff => {
+                self.font = Font::A;
+                self.width_multiplier = 1;
+            }
+            ESCPOSCommand::CharacterSize { width, .. } => {
+                self.width_multiplier = (*width).clamp(1, 8);
+            }
+            ESCPOSCommand::Initialize => {
+                self.font = Font::A;
+                self.width_multiplier = 1;
+                self.align = Align::Left;
+                self.code_page = 0;
+                self.left_margin_dots = 0;
+                self.print_area_width_dots = None;
+                self.character_spacing = 0;
+                self.update_line_width();
+            }
+            ESCPOSCommand::LeftMargin(dots) => {
+                self.left_margin_dots = *dots;
+                self.update_line_width();
+            }
+            ESCPOSCommand::PrintAreaWidth(dots) => {
+                self.print_area_width_dots = Some(*dots);
+                self.update_line_width();
+            }
+            ESCPOSCommand::CharacterSpacing(dots) => self.character_spacing = *dots,
+            ESCPOSCommand::CodePage(n) => self.code_page = *n,
+            ESCPOSCommand::KanjiModeOn => self.kanji = true,
+            ESCPOSCommand::KanjiModeOff => self.kanji = false,
+            ESCPOSCommand::Encoding(encoding) => {
+                self.encoding = *encoding;
+                self.kanji = encoding.double_byte().is_some();
+            }
+            _ => {}
+        }
+    }
+
+    /// The print area starts at the left margin and ends at the right edge or after its width
+    fn update_line_width(&mut self) {
+        let available = self.print_width_dots.saturating_sub(self.left_margin_dots);
+        self.line_width_dots = match self.print_area_width_dots {
+            Some(width) => width.min(available),
+            None => available,
+        };
+    }
+
+    /// Characters of the current font, size and spacing that fit on a line
+    pub fn columns(&self, profile: &PrinterProfile) -> usize {
+        let font_columns = profile.columns(self.font).max(1);
+        let char_dots = (profile.print_width_dots / u16::from(font_columns)).max(1)
+            + u16::from(self.character_spacing);
+        let columns = self.line_width_dots / (char_dots * u16::from(self.width_multiplier.max(1)));
+        usize::from(columns.max(1))
+    }
+}
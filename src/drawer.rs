@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use crate::{
+    commands::command::{DrawerPin, ESCPOSBuilderTrait, ESCPOSCommand},
+    status::{StatusQuery, StatusTrait},
+    EcoPrintResult, TransportTrait,
+};
+
+#[allow(async_fn_in_trait)]
+/// Cash drawer connected to the printer drawer kick-out connector.
+/// The pulse is sent right away, not queued with a print job.
+pub trait DrawerTrait {
+    /// Open the drawer with ESC p and the default pulse (see ESCPOSCommand::drawer_kick)
+    async fn open_drawer(&mut self, pin: DrawerPin) -> EcoPrintResult<()>;
+
+    /// Open the drawer with DLE DC4, which also works when the printer is offline
+    async fn open_drawer_realtime(&mut self, pin: DrawerPin, pulse_ms: u16) -> EcoPrintResult<()>;
+
+    /// Read the drawer state from the printer status (DLE EOT 1)
+    async fn drawer_open(&mut self, timeout: Duration) -> EcoPrintResult<bool>;
+}
+
+impl<T: TransportTrait> DrawerTrait for T {
+    async fn open_drawer(&mut self, pin: DrawerPin) -> EcoPrintResult<()> {
+        self.write(&ESCPOSCommand::drawer_kick(pin).to_escpos_bytes())
+            .await
+    }
+
+    async fn open_drawer_realtime(&mut self, pin: DrawerPin, pulse_ms: u16) -> EcoPrintResult<()> {
+        let cmd = ESCPOSCommand::DrawerKickRealTime { pin, pulse_ms };
+        self.write(&cmd.to_escpos_bytes()).await
+    }
+
+    async fn drawer_open(&mut self, timeout: Duration) -> EcoPrintResult<bool> {
+        let status = self.status_of(&[StatusQuery::Printer], timeout).await?;
+        Ok(status.drawer_open)
+    }
+}
@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use crate::{profile::PrinterProfile, EcoPrintError, EcoPrintResult, TransportTrait};
+
+/// Printer information request (GS I n).
+/// ModelId, TypeId and VersionId reply a single byte, the others reply a text framed as `_ ... NUL`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdentityQuery {
+    ModelId = 1,
+    TypeId = 2,
+    VersionId = 3,
+    Firmware = 65,
+    Manufacturer = 66,
+    ModelName = 67,
+    Serial = 68,
+}
+
+impl IdentityQuery {
+    pub const ALL: [IdentityQuery; 7] = [
+        IdentityQuery::ModelId,
+        IdentityQuery::TypeId,
+        IdentityQuery::VersionId,
+        IdentityQuery::Firmware,
+        IdentityQuery::Manufacturer,
+        IdentityQuery::ModelName,
+        IdentityQuery::Serial,
+    ];
+
+    pub fn to_escpos(&self) -> Vec<u8> {
+        vec![0x1D, 0x49, *self as u8]
+    }
+
+    pub fn is_text(&self) -> bool {
+        (*self as u8) >= 65
+    }
+}
+
+/// Reply to an IdentityQuery
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdentityReply {
+    Byte(u8),
+    Text(String),
+}
+
+/// What the printer says it is.
+/// Fields are None when the printer doesn't reply to the query.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrinterIdentity {
+    pub model_id: Option<u8>,
+    pub type_id: Option<u8>,
+    pub version_id: Option<u8>,
+    pub firmware: Option<String>,
+    pub manufacturer: Option<String>,
+    pub model_name: Option<String>,
+    pub serial: Option<String>,
+}
+
+impl PrinterIdentity {
+    pub fn apply(&mut self, query: IdentityQuery, reply: IdentityReply) {
+        match (query, reply) {
+            (IdentityQuery::ModelId, IdentityReply::Byte(byte)) => self.model_id = Some(byte),
+            (IdentityQuery::TypeId, IdentityReply::Byte(byte)) => self.type_id = Some(byte),
+            (IdentityQuery::VersionId, IdentityReply::Byte(byte)) => self.version_id = Some(byte),
+            (IdentityQuery::Firmware, IdentityReply::Text(text)) => self.firmware = Some(text),
+            (IdentityQuery::Manufacturer, IdentityReply::Text(text)) => {
+                self.manufacturer = Some(text)
+            }
+            (IdentityQuery::ModelName, IdentityReply::Text(text)) => self.model_name = Some(text),
+            (IdentityQuery::Serial, IdentityReply::Text(text)) => self.serial = Some(text),
+            (query, reply) => log::warn!("Unexpected reply {:?} to {:?}", reply, query),
+        }
+    }
+
+    /// Autocutter bit of the type ID, None if the printer didn't reply it
+    pub fn has_cutter(&self) -> Option<bool> {
+        self.type_id.map(|type_id| type_id & 0x02 != 0)
+    }
+
+    /// Multi-byte character codes bit of the type ID, None if the printer didn't reply it
+    pub fn supports_multibyte(&self) -> Option<bool> {
+        self.type_id.map(|type_id| type_id & 0x01 != 0)
+    }
+}
+
+#[allow(async_fn_in_trait)]
+/// Printer identification queries, available on every transport that can read the printer replies.
+pub trait IdentifyTrait {
+    /// Send a GS I query and wait for its reply
+    async fn query_identity(
+        &mut self,
+        query: IdentityQuery,
+        timeout: Duration,
+    ) -> EcoPrintResult<IdentityReply>;
+
+    /// Send every GS I query, the ones the printer doesn't reply before the timeout are left as None
+    async fn identify(&mut self, timeout: Duration) -> EcoPrintResult<PrinterIdentity> {
+        let mut identity = PrinterIdentity::default();
+        for query in IdentityQuery::ALL {
+            match self.query_identity(query, timeout).await {
+                Ok(reply) => identity.apply(query, reply),
+                Err(EcoPrintError::Timeout(_)) => log::info!("{:?} not supported", query),
+                Err(_err) => return Err(_err),
+            }
+        }
+        Ok(identity)
+    }
+
+    /// Identify the printer and pick the profile that matches it
+    async fn detect_profile(&mut self, timeout: Duration) -> EcoPrintResult<PrinterProfile> {
+        let identity = self.identify(timeout).await?;
+        Ok(PrinterProfile::from_identity(&identity))
+    }
+}
+
+impl<T: TransportTrait> IdentifyTrait for T {
+    async fn query_identity(
+        &mut self,
+        query: IdentityQuery,
+        timeout: Duration,
+    ) -> EcoPrintResult<IdentityReply> {
+        self.write(&query.to_escpos()).await?;
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut reply = Vec::new();
+        loop {
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            reply.extend(self.read(remaining).await?);
+
+            if !query.is_text() {
+                if let Some(byte) = reply.first() {
+                    return Ok(IdentityReply::Byte(*byte));
+                }
+                continue;
+            }
+            // The text may arrive split across reads, wait for the NUL
+            if let Some(start) = reply.iter().position(|byte| *byte == b'_') {
+                if let Some(len) = reply[start..].iter().position(|byte| *byte == 0x00) {
+                    let text = String::from_utf8_lossy(&reply[start + 1..start + len]);
+                    return Ok(IdentityReply::Text(text.trim().to_string()));
+                }
+            }
+        }
+    }
+}
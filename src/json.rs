@@ -0,0 +1,255 @@
+//! JSON print jobs, so jobs can be built outside Rust (e.g.: in JS through the Tauri plugin) and printed here.
+//!
+//! A job is an ESCPOSBuilder serialized as an object with the schema version:
+//! ```json
+//! {
+//!   "version": 1,
+//!   "profile": { "name": "XP-58", "buzzer": "esc_b", "cutter": "none" },
+//!   "initialize": true,
+//!   "reset_at_end": false,
+//!   "commands": [
+//!     { "command": "align_center" },
+//!     { "command": { "character_size": { "width": 2, "height": 2 } } },
+//!     { "text": "Corner Coffee\n" },
+//!     { "command": { "character_size": { "width": 1, "height": 1 } } },
+//!     { "wrapped": { "text": "12 Main St.", "hanging_indent": 0 } },
+//!     { "command": "align_left" },
+//!     { "spacing": { "line_spacing": { "mm": 3.0 } } },
+//!     { "spacing": { "left_margin": { "dots": 16 } } },
+//!     { "table": {
+//!         "columns": [
+//!           { "width": "auto", "align": "left", "fill": "." },
+//!           { "width": { "fixed": 8 }, "align": "right", "fill": " " }
+//!         ],
+//!         "rows": [["Latte", "7.00"]],
+//!         "overflow": "truncate",
+//!         "gap": 1
+//!     } },
+//!     { "image": { "width": 8, "height": 2, "raster": "/4E=" } },
+//!     { "image": { "encoded": "<base64 PNG/JPEG file>" } },
+//!     { "qr_code": { "data": "https://example.com", "module_size": 6, "error_correction": "m" } },
+//!     { "barcode": { "system": "ean13", "data": "4006381333931" } },
+//!     { "command": { "print_nv_graphic": { "key": "LG", "scale_x": 1, "scale_y": 1 } } },
+//!     { "beep": { "count": 2, "duration_ms": 200 } },
+//!     { "cut": "partial" }
+//!   ]
+//! }
+//! ```
+//! - Enum variants are snake_case: unit variants are strings, the others are objects with one key.
+//! - `profile` fields missing from the job take the value of PrinterProfile::generic(), `profile` can be left out.
+//! - Images are packed rows of 1 bit per dot (MSB leftmost, 1 is black) in base64,
+//!   or an encoded image file in base64 that is converted when the job is read.
+//! - Barcode `height` (80), `module_width` (3) and `hri` ("below") are optional.
+//!
+//! The version is increased when a change breaks the jobs written for the previous version,
+//! jobs with a newer version are rejected.
+//! e.g.:
+//! ```rust
+//! use eco_print::commands::command::*;
+//!
+//! let mut builder = ESCPOSBuilder::default();
+//! builder.add_command(ESCPOSDataBuilder::Command(ESCPOSCommand::AlignCenter));
+//! builder.add_command(ESCPOSDataBuilder::Text("Hello World\n".to_string()));
+//! builder.add_command(ESCPOSDataBuilder::Cut(CutMode::Full));
+//!
+//! let json = builder.to_json().unwrap();
+//! let job = ESCPOSBuilder::from_json(&json).unwrap();
+//! assert_eq!(job.to_escpos_bytes(), builder.to_escpos_bytes());
+//! ```
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commands::{
+        barcode::{Barcode, BarcodeSystem, HriPosition},
+        command::{ESCPOSBuilder, ESCPOSBuilderTrait, ESCPOSDataBuilder},
+        image::ESCPOSImage,
+        qrcode::{ESCPOSQRCode, EcLevel},
+    },
+    profile::PrinterProfile,
+    EcoPrintError, EcoPrintResult,
+};
+
+/// Version of the JSON job schema written by this library
+pub const JOB_SCHEMA_VERSION: u32 = 1;
+
+impl ESCPOSBuilder {
+    pub fn to_json(&self) -> EcoPrintResult<String> {
+        serde_json::to_string(self)
+            .map_err(|_err| EcoPrintError::Json(format!("Error writing the job - {}", _err)))
+    }
+
+    pub fn from_json(json: &str) -> EcoPrintResult<Self> {
+        serde_json::from_str(json)
+            .map_err(|_err| EcoPrintError::Json(format!("Error reading the job - {}", _err)))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct JobJson {
+    version: u32,
+    #[serde(default)]
+    profile: PrinterProfile,
+    #[serde(default)]
+    initialize: bool,
+    #[serde(default)]
+    reset_at_end: bool,
+    commands: Vec<ESCPOSDataBuilder>,
+}
+
+impl From<ESCPOSBuilder> for JobJson {
+    fn from(builder: ESCPOSBuilder) -> Self {
+        Self {
+            version: JOB_SCHEMA_VERSION,
+            profile: builder.profile().clone(),
+            initialize: builder.initialize(),
+            reset_at_end: builder.reset_at_end(),
+            commands: builder.commands().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<JobJson> for ESCPOSBuilder {
+    type Error = EcoPrintError;
+
+    fn try_from(job: JobJson) -> EcoPrintResult<Self> {
+        if job.version == 0 || job.version > JOB_SCHEMA_VERSION {
+            return Err(EcoPrintError::Json(format!(
+                "Job version {} is not supported, the latest version is {}",
+                job.version, JOB_SCHEMA_VERSION
+            )));
+        }
+        let mut builder = ESCPOSBuilder::new(job.profile);
+        builder.set_initialize(job.initialize);
+        builder.set_reset_at_end(job.reset_at_end);
+        builder.add_commands(job.commands);
+        Ok(builder)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum ImageJson {
+    Raster {
+        width: u32,
+        height: u32,
+        raster: String,
+    },
+    Encoded {
+        encoded: String,
+    },
+}
+
+fn decode_base64(data: &str) -> EcoPrintResult<Vec<u8>> {
+    STANDARD
+        .decode(data)
+        .map_err(|_err| EcoPrintError::Json(format!("Invalid base64 - {}", _err)))
+}
+
+impl From<ESCPOSImage> for ImageJson {
+    fn from(image: ESCPOSImage) -> Self {
+        ImageJson::Raster {
+            width: image.width(),
+            height: image.height(),
+            raster: STANDARD.encode(image.raster()),
+        }
+    }
+}
+
+impl TryFrom<ImageJson> for ESCPOSImage {
+    type Error = EcoPrintError;
+
+    fn try_from(image: ImageJson) -> EcoPrintResult<Self> {
+        match image {
+            ImageJson::Raster {
+                width,
+                height,
+                raster,
+            } => ESCPOSImage::from_raster(width, height, decode_base64(&raster)?),
+            ImageJson::Encoded { encoded } => ESCPOSImage::from_bytes(&decode_base64(&encoded)?),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum EcLevelJson {
+    L,
+    M,
+    Q,
+    H,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct QrCodeJson {
+    data: String,
+    #[serde(default = "default_module_size")]
+    module_size: u8,
+    #[serde(default = "default_error_correction")]
+    error_correction: EcLevelJson,
+}
+
+fn default_module_size() -> u8 {
+    4
+}
+
+fn default_error_correction() -> EcLevelJson {
+    EcLevelJson::M
+}
+
+impl From<ESCPOSQRCode> for QrCodeJson {
+    fn from(qrcode: ESCPOSQRCode) -> Self {
+        Self {
+            data: qrcode.data().to_string(),
+            module_size: qrcode.module_size(),
+            error_correction: match qrcode.qrcode.error_correction_level() {
+                EcLevel::L => EcLevelJson::L,
+                EcLevel::M => EcLevelJson::M,
+                EcLevel::Q => EcLevelJson::Q,
+                EcLevel::H => EcLevelJson::H,
+            },
+        }
+    }
+}
+
+impl TryFrom<QrCodeJson> for ESCPOSQRCode {
+    type Error = EcoPrintError;
+
+    fn try_from(qrcode: QrCodeJson) -> EcoPrintResult<Self> {
+        let level = match qrcode.error_correction {
+            EcLevelJson::L => EcLevel::L,
+            EcLevelJson::M => EcLevel::M,
+            EcLevelJson::Q => EcLevel::Q,
+            EcLevelJson::H => EcLevel::H,
+        };
+        Ok(ESCPOSQRCode::new(qrcode.data)?
+            .with_error_correction(level)?
+            .with_module_size(qrcode.module_size))
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct BarcodeJson {
+    system: BarcodeSystem,
+    data: String,
+    height: Option<u8>,
+    module_width: Option<u8>,
+    #[serde(default)]
+    hri: HriPosition,
+}
+
+impl TryFrom<BarcodeJson> for Barcode {
+    type Error = EcoPrintError;
+
+    fn try_from(barcode: BarcodeJson) -> EcoPrintResult<Self> {
+        let mut result = Barcode::new(barcode.system, barcode.data)?.with_hri(barcode.hri);
+        if let Some(height) = barcode.height {
+            result = result.with_height(height);
+        }
+        if let Some(module_width) = barcode.module_width {
+            result = result.with_module_width(module_width);
+        }
+        Ok(result)
+    }
+}
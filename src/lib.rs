@@ -1,4 +1,4 @@
-#[allow(unused)]
+#[allow(unused_imports)]
 #[macro_use]
 extern crate log;
 #[cfg(feature = "ble")]
@@ -7,6 +7,18 @@ pub mod ble;
 pub mod usb;
 
 pub mod commands;
+pub mod drawer;
+pub mod identify;
+pub mod json;
+pub mod markup;
+pub mod nv_graphics;
+mod printer_id;
+pub mod profile;
+pub mod receipt;
+pub mod status;
+pub mod template;
+
+pub use printer_id::{PrinterHandle, PrinterId};
 
 #[cfg(feature = "ble")]
 pub use btleplug;
@@ -55,6 +67,36 @@ pub enum EcoPrintError {
     Printing(String),
     #[error("Send to printer error: {0}")]
     Send(String),
+    #[error("Invalid printer id: {0}")]
+    PrinterId(String),
+    #[error("Error on printer status: {0}")]
+    Status(String),
+    #[error("Printer out of paper")]
+    PaperOut,
+    #[error("Timeout: {0}")]
+    Timeout(String),
+    #[error("Error on image: {0}")]
+    Image(String),
+    #[error("Error on font: {0}")]
+    Font(String),
+    #[error("Error on layout: {0}")]
+    Layout(String),
+    #[error("Error on QR code: {0}")]
+    QrCode(String),
+    #[error("Error on barcode: {0}")]
+    Barcode(String),
+    #[error("Error on JSON: {0}")]
+    Json(String),
+    #[error("Error on template: {0}")]
+    Template(String),
+    #[error("Error on markup at line {line}, column {column}: {message}")]
+    Markup {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("Not supported by the printer: {0}")]
+    Unsupported(String),
 
     // Android JNI
     #[cfg(target_os = "android")]
@@ -70,7 +112,7 @@ impl Serialize for EcoPrintError {
     where
         S: Serializer,
     {
-        let msg = format!("{}", self.to_string());
+        let msg = self.to_string();
         log::error!("{}", msg);
         serializer.serialize_str(&msg)
     }
@@ -84,19 +126,47 @@ pub trait PrinterTrait {
     /// Print data to the printer
     /// the data parameter is a string that contains the data to be printed, which can be text, commands, etc.
     /// the data parameter contains the escpos commands already formatted.
-    /// Use ESCPOSBuilder to generate the data parameter.
+    /// Only ASCII data is sent as it is, use print_bytes for the output of ESCPOSBuilder.
+    async fn print(&mut self, data: String) -> crate::EcoPrintResult<()>;
+
+    /// Print the bytes generated by ESCPOSBuilder: text encoded with the code page of the printer,
+    /// images and commands with parameters above 0x7F.
     /// e.g.:
-    /// ```rust
-    /// let mut builder = ESCPOSBuilder::new();
+    /// ```rust,no_run
+    /// # use eco_print::{commands::command::*, PrinterTrait};
+    /// # async fn example(printer: &mut impl PrinterTrait) -> eco_print::EcoPrintResult<()> {
+    /// let mut builder = ESCPOSBuilder::default();
     /// builder.add_command(ESCPOSDataBuilder::Command(ESCPOSCommand::LineFeed));
     /// builder.add_command(ESCPOSDataBuilder::Command(ESCPOSCommand::FontBold));
     /// builder.add_command(ESCPOSDataBuilder::Text("Hello World".to_string()));
-    /// let data = builder.to_escpos();
-    /// //printer implements PrinterTrait
-    /// let printer = Printer::new();
-    /// printer.print(data);
+    /// let data = builder.to_escpos_bytes();
+    /// // printer implements PrinterTrait
+    /// printer.print_bytes(data).await?;
+    /// # Ok(())
+    /// # }
     /// ```
-    async fn print(&mut self, data: String) -> crate::EcoPrintResult<()>;
+    ///
+    /// Not Required, printers that only implement print receive the data when it's valid UTF-8,
+    /// the other data is refused instead of being altered.
+    async fn print_bytes(&mut self, data: Vec<u8>) -> crate::EcoPrintResult<()> {
+        let data = String::from_utf8(data).map_err(|_err| {
+            EcoPrintError::Printing(format!(
+                "Bytes above 0x7F need print_bytes support from the printer - {}",
+                _err
+            ))
+        })?;
+        self.print(data).await
+    }
+}
+
+#[allow(async_fn_in_trait)]
+/// Raw access to a connection that can both send bytes to the printer and read its replies.
+/// Status, identification and other queries are built on top of it.
+pub trait TransportTrait {
+    /// Send the bytes as they are, without any formatting
+    async fn write(&mut self, data: &[u8]) -> crate::EcoPrintResult<()>;
+    /// Wait for the next bytes sent by the printer, failing if nothing arrives before the timeout
+    async fn read(&mut self, timeout: std::time::Duration) -> crate::EcoPrintResult<Vec<u8>>;
 }
 
 #[allow(async_fn_in_trait)]
@@ -113,8 +183,37 @@ pub trait FinderTrait<Device> {
 
     /// Return the stream of devices found
     async fn scan(&mut self) -> crate::EcoPrintResult<()>;
+    /// Stop the scan started by scan.
+    /// Not Required, the finders whose scan stops by itself keep this default
+    async fn stop_scan(&mut self) -> crate::EcoPrintResult<()> {
+        Ok(())
+    }
+    /// Scan for a bounded time, stop the scan and return the devices found
+    async fn scan_for(
+        &mut self,
+        duration: std::time::Duration,
+    ) -> crate::EcoPrintResult<Vec<Device>> {
+        self.scan().await?;
+        tokio::time::sleep(duration).await;
+        self.stop_scan().await?;
+        Ok(self.get_devices().await)
+    }
     /// Connect to a device and save the connection on the struct
     async fn connect(&mut self, device: Device) -> crate::EcoPrintResult<()>;
+    /// Connect to a device by an id stored on a previous session.
+    /// If the device is not known yet, a short discovery is done until it's found or the timeout expires.
+    /// Not Required, only the BLE finder connects by id today: the USB (`VID:PID:serial`)
+    /// and network (`host:port`) ids are parsed but their finders return Unsupported.
+    async fn connect_by_id(
+        &mut self,
+        id: &PrinterId,
+        _timeout: std::time::Duration,
+    ) -> crate::EcoPrintResult<PrinterHandle> {
+        Err(EcoPrintError::Unsupported(format!(
+            "Connect by id {} on this finder",
+            id
+        )))
+    }
     /// Disconnect from the device that is connected on the struct
     async fn disconnect(&mut self) -> crate::EcoPrintResult<()>;
 }
@@ -0,0 +1,855 @@
+use crate::{
+    commands::{
+        barcode::{Barcode, BarcodeSystem, HriPosition},
+        buzzer::Beep,
+        command::{CutMode, DrawerPin, ESCPOSCommand, ESCPOSDataBuilder},
+        image::ESCPOSImage,
+        qrcode::{ESCPOSQRCode, EcLevel},
+        table::{ColumnWidth, Overflow, Table, TableColumn},
+        text::{Align, TextBlock},
+    },
+    nv_graphics::NvKey,
+    EcoPrintError, EcoPrintResult,
+};
+
+/// Tags converted to builder entries
+pub(crate) const TAGS: [&str; 24] = [
+    "left", "center", "right", "b", "u", "inv", "small", "dh", "dw", "size", "br", "p", "hr",
+    "table", "row", "col", "qr", "barcode", "img", "nv", "cut", "beep", "feed", "drawer",
+];
+
+/// Elements without content, they can be written as `<br>` or `<br/>`
+const VOID_ELEMENTS: [&str; 8] = ["br", "img", "nv", "cut", "beep", "feed", "drawer", "hr"];
+
+/// Line and column (starting at 1) of a markup node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    pub fn error(&self, message: impl Into<String>) -> EcoPrintError {
+        EcoPrintError::Markup {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Node {
+    Text {
+        text: String,
+        pos: Position,
+    },
+    Element {
+        name: String,
+        attrs: Vec<(String, String)>,
+        children: Vec<Node>,
+        pos: Position,
+    },
+    /// Entries converted beforehand, e.g.: the static parts of a template
+    Commands {
+        cmds: Vec<ESCPOSDataBuilder>,
+        pos: Position,
+    },
+}
+
+/// Parse the markup into builder entries.
+///
+/// Tags:
+/// - `<left>`, `<center>`, `<right>` - Alignment of the content
+/// - `<b>`, `<u>`, `<inv>` (white on black), `<small>` (font B), `<dh>`, `<dw>`, `<size w=2 h=3>` - Text style
+/// - `<br>` - Line feed
+/// - `<p indent=2>` - Text wrapped to the line, with hanging indent
+/// - `<hr char=->` - Line of characters as wide as the paper
+/// - `<row fill=.><col width=auto|50%|8 align=right>` - Table row, `<table overflow=wrap>` shares the column widths of its rows
+/// - `<qr size=6 ec=M>`, `<barcode type=ean13 height=80 width=3 hri=below>` - Codes with the content as data
+/// - `<img src=logo.png>`, `<nv key=LG>` - Image from a file or a NV graphic
+/// - `<cut>`, `<cut mode=partial>`, `<beep count=2 duration=200>`, `<feed dots=24>`, `<feed lines=2>`, `<drawer pin=2>`
+///
+/// Whitespace between tags spanning several lines is ignored, so layouts can be indented.
+/// Use `&lt;`, `&gt;`, `&amp;` and `&quot;` to write those characters in text.
+/// e.g.:
+/// ```rust
+/// use eco_print::markup;
+///
+/// let cmds = markup::parse("<center><b>Corner Coffee</b></center><br/><cut/>").unwrap();
+///
+/// let err = markup::parse("<center>\n  <b>Title</center>").unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "Error on markup at line 2, column 11: Expected </b> but found </center>"
+/// );
+/// ```
+pub fn parse(markup: &str) -> EcoPrintResult<Vec<ESCPOSDataBuilder>> {
+    let nodes = parse_nodes(markup)?;
+    to_commands(&nodes)
+}
+
+pub(crate) fn parse_nodes(markup: &str) -> EcoPrintResult<Vec<Node>> {
+    let mut parser = Parser {
+        chars: markup.chars().collect(),
+        i: 0,
+        pos: Position { line: 1, column: 1 },
+    };
+    parser.children(None)
+}
+
+pub(crate) fn to_commands(nodes: &[Node]) -> EcoPrintResult<Vec<ESCPOSDataBuilder>> {
+    let mut converter = Converter::default();
+    converter.nodes(nodes)?;
+    converter.restore_align();
+    Ok(converter.cmds)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    i: usize,
+    pos: Position,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.i).copied()
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars()
+            .enumerate()
+            .all(|(n, c)| self.chars.get(self.i + n) == Some(&c))
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.i += 1;
+        if c == '\n' {
+            self.pos.line += 1;
+            self.pos.column = 1;
+        } else {
+            self.pos.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn expect(&mut self, c: char) -> EcoPrintResult<()> {
+        match self.peek() {
+            Some(found) if found == c => {
+                self.bump();
+                Ok(())
+            }
+            Some(found) => Err(self
+                .pos
+                .error(format!("Expected '{}' but found '{}'", c, found))),
+            None => Err(self
+                .pos
+                .error(format!("Expected '{}' but found the end", c))),
+        }
+    }
+
+    fn name(&mut self) -> EcoPrintResult<String> {
+        let mut name = String::new();
+        while let Some(c) = self
+            .peek()
+            .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+        {
+            name.push(c.to_ascii_lowercase());
+            self.bump();
+        }
+        if name.is_empty() {
+            return Err(self.pos.error("Expected a name"));
+        }
+        Ok(name)
+    }
+
+    /// Nodes until the end, or until the closing tag of `parent`
+    fn children(&mut self, parent: Option<(&str, Position)>) -> EcoPrintResult<Vec<Node>> {
+        let mut nodes = Vec::new();
+        loop {
+            if self.peek().is_none() {
+                return match parent {
+                    Some((name, pos)) => Err(pos.error(format!("<{}> is never closed", name))),
+                    None => Ok(nodes),
+                };
+            }
+            if self.starts_with("<!--") {
+                self.comment()?;
+            } else if self.starts_with("</") {
+                let pos = self.pos;
+                self.bump();
+                self.bump();
+                let name = self.name()?;
+                self.skip_whitespace();
+                self.expect('>')?;
+                return match parent {
+                    Some((parent, _)) if parent == name => Ok(nodes),
+                    Some((parent, _)) => {
+                        Err(pos.error(format!("Expected </{}> but found </{}>", parent, name)))
+                    }
+                    None => Err(pos.error(format!("</{}> was never opened", name))),
+                };
+            } else if self.peek() == Some('<') {
+                nodes.push(self.element()?);
+            } else {
+                let pos = self.pos;
+                let text = self.text()?;
+                // Indentation between tags
+                if !(text.trim().is_empty() && text.contains('\n')) {
+                    nodes.push(Node::Text { text, pos });
+                }
+            }
+        }
+    }
+
+    fn comment(&mut self) -> EcoPrintResult<()> {
+        let pos = self.pos;
+        while !self.starts_with("-->") {
+            if self.bump().is_none() {
+                return Err(pos.error("Comment is never closed"));
+            }
+        }
+        for _ in 0..3 {
+            self.bump();
+        }
+        Ok(())
+    }
+
+    fn element(&mut self) -> EcoPrintResult<Node> {
+        let pos = self.pos;
+        self.expect('<')?;
+        let name = self.name()?;
+        let mut attrs = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('>') => {
+                    self.bump();
+                    let children = if VOID_ELEMENTS.contains(&name.as_str()) {
+                        Vec::new()
+                    } else {
+                        self.children(Some((&name, pos)))?
+                    };
+                    return Ok(Node::Element {
+                        name,
+                        attrs,
+                        children,
+                        pos,
+                    });
+                }
+                Some('/') => {
+                    self.bump();
+                    self.expect('>')?;
+                    return Ok(Node::Element {
+                        name,
+                        attrs,
+                        children: Vec::new(),
+                        pos,
+                    });
+                }
+                Some(_) => attrs.push(self.attribute()?),
+                None => return Err(pos.error(format!("<{}> is never closed", name))),
+            }
+        }
+    }
+
+    /// name, name=value, name="value" or name='value'
+    fn attribute(&mut self) -> EcoPrintResult<(String, String)> {
+        let name = self.name()?;
+        self.skip_whitespace();
+        if self.peek() != Some('=') {
+            return Ok((name, String::new()));
+        }
+        self.bump();
+        self.skip_whitespace();
+
+        let mut value = String::new();
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                let pos = self.pos;
+                self.bump();
+                loop {
+                    match self.bump() {
+                        Some(c) if c == quote => break,
+                        Some('&') => value.push(self.entity()?),
+                        Some(c) => value.push(c),
+                        None => return Err(pos.error("Attribute value is never closed")),
+                    }
+                }
+            }
+            _ => {
+                while let Some(c) = self.peek() {
+                    if c.is_whitespace() || c == '>' || self.starts_with("/>") {
+                        break;
+                    }
+                    value.push(c);
+                    self.bump();
+                }
+            }
+        }
+        Ok((name, value))
+    }
+
+    fn text(&mut self) -> EcoPrintResult<String> {
+        let mut text = String::new();
+        while let Some(c) = self.peek().filter(|c| *c != '<') {
+            self.bump();
+            if c == '&' {
+                text.push(self.entity()?);
+            } else {
+                text.push(c);
+            }
+        }
+        Ok(text)
+    }
+
+    /// Entity after the '&'
+    fn entity(&mut self) -> EcoPrintResult<char> {
+        let pos = self.pos;
+        let mut name = String::new();
+        while let Some(c) = self.bump() {
+            if c == ';' {
+                return match name.as_str() {
+                    "lt" => Ok('<'),
+                    "gt" => Ok('>'),
+                    "amp" => Ok('&'),
+                    "quot" => Ok('"'),
+                    "apos" => Ok('\''),
+                    _ => name
+                        .strip_prefix('#')
+                        .and_then(|code| code.parse().ok())
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| pos.error(format!("Unknown entity &{};", name))),
+                };
+            }
+            if name.len() > 8 {
+                break;
+            }
+            name.push(c);
+        }
+        Err(pos.error("Entity without ';'"))
+    }
+}
+
+/// Converts the nodes to builder entries, keeping track of the styles to restore them when the tags close
+#[derive(Default)]
+struct Converter {
+    cmds: Vec<ESCPOSDataBuilder>,
+    aligns: Vec<Align>,
+    sizes: Vec<(u8, u8)>,
+    bold: usize,
+    underline: usize,
+    reverse: usize,
+    small: usize,
+    /// Text was printed since the last line feed
+    line_open: bool,
+    /// Alignment to restore at the start of the next line, ESC a is ignored in the middle of a line
+    pending_align: Option<Align>,
+}
+
+impl Converter {
+    fn push(&mut self, cmd: ESCPOSCommand) {
+        self.emit(ESCPOSDataBuilder::Command(cmd));
+    }
+
+    /// Add an entry, restoring the alignment of a closed tag once the line ends
+    fn emit(&mut self, entry: ESCPOSDataBuilder) {
+        if let (Some(_), ESCPOSDataBuilder::Text(text)) = (self.pending_align, &entry) {
+            if let Some(end) = text.find('\n') {
+                let (line, rest) = text.split_at(end + 1);
+                let rest = rest.to_string();
+                self.cmds.push(ESCPOSDataBuilder::Text(line.to_string()));
+                self.line_open = false;
+                self.restore_align();
+                if !rest.is_empty() {
+                    self.emit(ESCPOSDataBuilder::Text(rest));
+                }
+                return;
+            }
+        }
+        match &entry {
+            ESCPOSDataBuilder::Text(text) if !text.is_empty() => {
+                self.line_open = !text.ends_with('\n');
+            }
+            ESCPOSDataBuilder::Command(
+                ESCPOSCommand::LineFeed
+                | ESCPOSCommand::FeedDots(_)
+                | ESCPOSCommand::FeedAndCut { .. },
+            )
+            | ESCPOSDataBuilder::Wrapped(_)
+            | ESCPOSDataBuilder::Table(_)
+            | ESCPOSDataBuilder::Image(_)
+            | ESCPOSDataBuilder::QrCode(_)
+            | ESCPOSDataBuilder::Barcode(_)
+            | ESCPOSDataBuilder::Cut(_)
+            | ESCPOSDataBuilder::Page(_) => self.line_open = false,
+            _ => {}
+        }
+        self.cmds.push(entry);
+        if !self.line_open {
+            self.restore_align();
+        }
+    }
+
+    fn restore_align(&mut self) {
+        if let Some(align) = self.pending_align.take() {
+            self.cmds
+                .push(ESCPOSDataBuilder::Command(align_command(align)));
+        }
+    }
+
+    fn nodes(&mut self, nodes: &[Node]) -> EcoPrintResult<()> {
+        for node in nodes {
+            match node {
+                Node::Text { text, .. } => self.emit(ESCPOSDataBuilder::Text(text.clone())),
+                Node::Element {
+                    name,
+                    attrs,
+                    children,
+                    pos,
+                } => self.element(name, &Attrs { attrs, pos: *pos }, children)?,
+                Node::Commands { cmds, .. } => {
+                    for cmd in cmds {
+                        self.emit(cmd.clone());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn element(&mut self, name: &str, attrs: &Attrs, children: &[Node]) -> EcoPrintResult<()> {
+        let pos = attrs.pos;
+        match name {
+            "left" | "center" | "right" => {
+                let align = match name {
+                    "left" => Align::Left,
+                    "center" => Align::Center,
+                    _ => Align::Right,
+                };
+                self.aligns.push(align);
+                self.pending_align = None;
+                self.push(align_command(align));
+                self.nodes(children)?;
+                self.aligns.pop();
+                // Restored at the start of the next line, e.g.: after the <br/> following the tag
+                self.pending_align = Some(self.aligns.last().copied().unwrap_or_default());
+                if !self.line_open {
+                    self.restore_align();
+                }
+            }
+            "b" => {
+                self.bold += 1;
+                self.push(ESCPOSCommand::EmphasizeOn);
+                self.nodes(children)?;
+                self.bold -= 1;
+                if self.bold == 0 {
+                    self.push(ESCPOSCommand::EmphasizeOff);
+                }
+            }
+            "u" => {
+                self.underline += 1;
+                self.push(ESCPOSCommand::Underline);
+                self.nodes(children)?;
+                self.underline -= 1;
+                if self.underline == 0 {
+                    self.push(ESCPOSCommand::UnderlineOff);
+                }
+            }
+            "inv" => {
+                self.reverse += 1;
+                self.push(ESCPOSCommand::ReverseOn);
+                self.nodes(children)?;
+                self.reverse -= 1;
+                if self.reverse == 0 {
+                    self.push(ESCPOSCommand::ReverseOff);
+                }
+            }
+            "small" => {
+                self.small += 1;
+                self.push(ESCPOSCommand::FontB);
+                self.nodes(children)?;
+                self.small -= 1;
+                if self.small == 0 {
+                    self.push(ESCPOSCommand::FontA);
+                }
+            }
+            "dh" | "dw" | "size" => {
+                let (width, height) = self.sizes.last().copied().unwrap_or((1, 1));
+                let size = match name {
+                    "dh" => (width, 2),
+                    "dw" => (2, height),
+                    _ => (attrs.number("w", width)?, attrs.number("h", height)?),
+                };
+                self.sizes.push(size);
+                self.push(ESCPOSCommand::CharacterSize {
+                    width: size.0,
+                    height: size.1,
+                });
+                self.nodes(children)?;
+                self.sizes.pop();
+                let (width, height) = self.sizes.last().copied().unwrap_or((1, 1));
+                self.push(ESCPOSCommand::CharacterSize { width, height });
+            }
+            "br" => self.push(ESCPOSCommand::LineFeed),
+            "p" => {
+                let block = TextBlock::new(text_of(name, children)?)
+                    .with_hanging_indent(attrs.number("indent", 0)?);
+                self.emit(ESCPOSDataBuilder::Wrapped(block));
+            }
+            "hr" => {
+                let fill = attrs.char("char", '-')?;
+                let mut table =
+                    Table::new(vec![TableColumn::new(ColumnWidth::Auto).with_fill(fill)]);
+                table.add_row(vec![""]);
+                self.emit(ESCPOSDataBuilder::Table(table));
+            }
+            "table" => {
+                let rows: Vec<_> = children
+                    .iter()
+                    .map(|child| match child {
+                        Node::Element {
+                            name,
+                            attrs,
+                            children,
+                            pos,
+                        } if name == "row" => Ok((Attrs { attrs, pos: *pos }, children.as_slice())),
+                        Node::Element { name, pos, .. } => {
+                            Err(pos.error(format!("<{}> is not allowed in <table>", name)))
+                        }
+                        Node::Text { pos, .. } | Node::Commands { pos, .. } => {
+                            Err(pos.error("Text is not allowed in <table>"))
+                        }
+                    })
+                    .collect::<EcoPrintResult<_>>()?;
+                self.table(attrs, &rows)?;
+            }
+            "row" => self.table(attrs, &[(Attrs { ..*attrs }, children)])?,
+            "col" => return Err(pos.error("<col> is only allowed in <row>")),
+            "qr" => {
+                let ec = attrs.ec_level()?;
+                let qrcode = ESCPOSQRCode::new(text_of(name, children)?)
+                    .and_then(|qrcode| qrcode.with_error_correction(ec))
+                    .map_err(|_err| pos.error(_err.to_string()))?
+                    .with_module_size(attrs.number("size", 4)?);
+                self.emit(ESCPOSDataBuilder::QrCode(qrcode));
+            }
+            "barcode" => {
+                let system = attrs.barcode_system()?;
+                let hri = attrs.hri()?;
+                let barcode = Barcode::new(system, text_of(name, children)?.trim())
+                    .map_err(|_err| pos.error(_err.to_string()))?
+                    .with_height(attrs.number("height", 80)?)
+                    .with_module_width(attrs.number("width", 3)?)
+                    .with_hri(hri);
+                self.emit(ESCPOSDataBuilder::Barcode(barcode));
+            }
+            "img" => {
+                let image = ESCPOSImage::from_path(attrs.required("src")?)
+                    .map_err(|_err| pos.error(_err.to_string()))?;
+                self.emit(ESCPOSDataBuilder::Image(image));
+            }
+            "nv" => {
+                let key = NvKey::new(attrs.required("key")?)
+                    .map_err(|_err| pos.error(_err.to_string()))?;
+                let scale = attrs.number("scale", 1)?;
+                self.push(ESCPOSCommand::PrintNvGraphic {
+                    key,
+                    scale_x: scale,
+                    scale_y: scale,
+                });
+            }
+            "cut" => {
+                self.emit(ESCPOSDataBuilder::Cut(attrs.cut_mode()?));
+            }
+            "beep" => {
+                let beep = Beep::new(attrs.number("count", 1)?, attrs.number("duration", 200)?)
+                    .with_pattern(attrs.number("pattern", 1)?);
+                self.emit(ESCPOSDataBuilder::Beep(beep));
+            }
+            "feed" => {
+                if attrs.get("dots").is_some() {
+                    self.push(ESCPOSCommand::FeedDots(attrs.number("dots", 0)?));
+                }
+                for _ in 0..attrs.number::<u8>("lines", 0)? {
+                    self.push(ESCPOSCommand::LineFeed);
+                }
+            }
+            "drawer" => {
+                self.push(ESCPOSCommand::drawer_kick(attrs.drawer_pin()?));
+            }
+            name => return Err(pos.error(format!("Unknown tag <{}>", name))),
+        }
+        Ok(())
+    }
+
+    /// Rows of <col>, the column widths, alignment and fill come from the first row
+    fn table(&mut self, attrs: &Attrs, rows: &[(Attrs, &[Node])]) -> EcoPrintResult<()> {
+        let overflow = attrs.overflow()?;
+        let mut columns = Vec::new();
+        let mut table_rows = Vec::new();
+
+        for (i, (row, cols)) in rows.iter().enumerate() {
+            let fill = row.char("fill", ' ')?;
+            let mut cells = Vec::new();
+            for col in cols.iter() {
+                let (name, attrs, children, pos) = match col {
+                    Node::Element {
+                        name,
+                        attrs,
+                        children,
+                        pos,
+                    } => (name, attrs, children, pos),
+                    Node::Text { pos, .. } | Node::Commands { pos, .. } => {
+                        return Err(pos.error("Text is not allowed in <row>, use <col>"));
+                    }
+                };
+                if name != "col" {
+                    return Err(pos.error(format!("<{}> is not allowed in <row>", name)));
+                }
+                let attrs = Attrs { attrs, pos: *pos };
+                if i == 0 {
+                    columns.push(
+                        TableColumn::new(attrs.width()?)
+                            .with_align(attrs.align()?)
+                            .with_fill(attrs.char("fill", fill)?),
+                    );
+                }
+                cells.push(text_of(name, children)?);
+            }
+            table_rows.push(cells);
+        }
+
+        let mut table = Table::new(columns)
+            .with_overflow(overflow)
+            .with_gap(attrs.number("gap", 1)?);
+        for cells in table_rows {
+            table.add_row(cells);
+        }
+        self.emit(ESCPOSDataBuilder::Table(table));
+        Ok(())
+    }
+}
+
+fn align_command(align: Align) -> ESCPOSCommand {
+    match align {
+        Align::Left => ESCPOSCommand::AlignLeft,
+        Align::Center => ESCPOSCommand::AlignCenter,
+        Align::Right => ESCPOSCommand::AlignRight,
+    }
+}
+
+/// Content of an element that only accepts text
+fn text_of(name: &str, children: &[Node]) -> EcoPrintResult<String> {
+    children
+        .iter()
+        .map(|child| match child {
+            Node::Text { text, .. } => Ok(text.as_str()),
+            Node::Element { pos, .. } | Node::Commands { pos, .. } => {
+                Err(pos.error(format!("Only text is allowed in <{}>", name)))
+            }
+        })
+        .collect()
+}
+
+struct Attrs<'a> {
+    attrs: &'a [(String, String)],
+    pos: Position,
+}
+
+impl Attrs<'_> {
+    fn get(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(attr, _)| attr == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn required(&self, name: &str) -> EcoPrintResult<&str> {
+        self.get(name)
+            .ok_or_else(|| self.pos.error(format!("Missing attribute {}", name)))
+    }
+
+    fn number<T: std::str::FromStr>(&self, name: &str, default: T) -> EcoPrintResult<T> {
+        match self.get(name) {
+            Some(value) => value.parse().map_err(|_| {
+                self.pos
+                    .error(format!("Invalid number {}={:?}", name, value))
+            }),
+            None => Ok(default),
+        }
+    }
+
+    fn char(&self, name: &str, default: char) -> EcoPrintResult<char> {
+        match self.get(name) {
+            Some(value) => {
+                let mut chars = value.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(c),
+                    _ => Err(self
+                        .pos
+                        .error(format!("{} must be a single character", name))),
+                }
+            }
+            None => Ok(default),
+        }
+    }
+
+    fn width(&self) -> EcoPrintResult<ColumnWidth> {
+        let width = self.get("width").unwrap_or("auto");
+        let invalid = || self.pos.error(format!("Invalid column width {:?}", width));
+        if width == "auto" {
+            Ok(ColumnWidth::Auto)
+        } else if let Some(percent) = width.strip_suffix('%') {
+            percent
+                .parse()
+                .map(ColumnWidth::Percent)
+                .map_err(|_| invalid())
+        } else {
+            width.parse().map(ColumnWidth::Fixed).map_err(|_| invalid())
+        }
+    }
+
+    fn align(&self) -> EcoPrintResult<Align> {
+        match self.get("align").unwrap_or("left") {
+            "left" => Ok(Align::Left),
+            "center" => Ok(Align::Center),
+            "right" => Ok(Align::Right),
+            align => Err(self.pos.error(format!("Invalid align {:?}", align))),
+        }
+    }
+
+    fn overflow(&self) -> EcoPrintResult<Overflow> {
+        match self.get("overflow").unwrap_or("truncate") {
+            "truncate" => Ok(Overflow::Truncate),
+            "wrap" => Ok(Overflow::Wrap),
+            overflow => Err(self.pos.error(format!("Invalid overflow {:?}", overflow))),
+        }
+    }
+
+    fn ec_level(&self) -> EcoPrintResult<EcLevel> {
+        match self.get("ec").unwrap_or("M").to_ascii_uppercase().as_str() {
+            "L" => Ok(EcLevel::L),
+            "M" => Ok(EcLevel::M),
+            "Q" => Ok(EcLevel::Q),
+            "H" => Ok(EcLevel::H),
+            ec => Err(self
+                .pos
+                .error(format!("Invalid QR error correction {:?}", ec))),
+        }
+    }
+
+    fn barcode_system(&self) -> EcoPrintResult<BarcodeSystem> {
+        match self.required("type")?.to_ascii_lowercase().as_str() {
+            "upca" | "upc-a" => Ok(BarcodeSystem::UpcA),
+            "upce" | "upc-e" => Ok(BarcodeSystem::UpcE),
+            "ean13" | "ean-13" => Ok(BarcodeSystem::Ean13),
+            "ean8" | "ean-8" => Ok(BarcodeSystem::Ean8),
+            "code39" => Ok(BarcodeSystem::Code39),
+            "itf" => Ok(BarcodeSystem::Itf),
+            "codabar" => Ok(BarcodeSystem::Codabar),
+            "code93" => Ok(BarcodeSystem::Code93),
+            "code128" => Ok(BarcodeSystem::Code128),
+            system => Err(self.pos.error(format!("Unknown barcode type {:?}", system))),
+        }
+    }
+
+    fn hri(&self) -> EcoPrintResult<HriPosition> {
+        match self.get("hri").unwrap_or("below") {
+            "none" => Ok(HriPosition::None),
+            "above" => Ok(HriPosition::Above),
+            "below" => Ok(HriPosition::Below),
+            "both" => Ok(HriPosition::Both),
+            hri => Err(self.pos.error(format!("Invalid barcode hri {:?}", hri))),
+        }
+    }
+
+    fn cut_mode(&self) -> EcoPrintResult<CutMode> {
+        match self.get("mode").unwrap_or("full") {
+            "full" => Ok(CutMode::Full),
+            "partial" => Ok(CutMode::Partial),
+            mode => Err(self.pos.error(format!("Invalid cut mode {:?}", mode))),
+        }
+    }
+
+    fn drawer_pin(&self) -> EcoPrintResult<DrawerPin> {
+        match self.get("pin").unwrap_or("2") {
+            "2" => Ok(DrawerPin::Pin2),
+            "5" => Ok(DrawerPin::Pin5),
+            pin => Err(self.pos.error(format!("Invalid drawer pin {:?}", pin))),
+        }
+    }
+}
+
+/// Check the attribute values of a tag without converting its content,
+/// e.g.: for the elements of a template whose content has placeholders
+pub(crate) fn check_attributes(
+    name: &str,
+    attrs: &[(String, String)],
+    pos: Position,
+) -> EcoPrintResult<()> {
+    let attrs = Attrs { attrs, pos };
+    match name {
+        "size" => {
+            attrs.number::<u8>("w", 1)?;
+            attrs.number::<u8>("h", 1)?;
+        }
+        "p" => {
+            attrs.number::<usize>("indent", 0)?;
+        }
+        "hr" => {
+            attrs.char("char", '-')?;
+        }
+        "table" | "row" => {
+            attrs.overflow()?;
+            attrs.number::<usize>("gap", 1)?;
+            attrs.char("fill", ' ')?;
+        }
+        "col" => {
+            attrs.width()?;
+            attrs.align()?;
+            attrs.char("fill", ' ')?;
+        }
+        "qr" => {
+            attrs.ec_level()?;
+            attrs.number::<u8>("size", 4)?;
+        }
+        "barcode" => {
+            attrs.barcode_system()?;
+            attrs.hri()?;
+            attrs.number::<u8>("height", 80)?;
+            attrs.number::<u8>("width", 3)?;
+        }
+        "img" => {
+            attrs.required("src")?;
+        }
+        "nv" => {
+            NvKey::new(attrs.required("key")?).map_err(|_err| pos.error(_err.to_string()))?;
+            attrs.number::<u8>("scale", 1)?;
+        }
+        "cut" => {
+            attrs.cut_mode()?;
+        }
+        "beep" => {
+            attrs.number::<u8>("count", 1)?;
+            attrs.number::<u16>("duration", 200)?;
+            attrs.number::<u8>("pattern", 1)?;
+        }
+        "feed" => {
+            attrs.number::<u8>("dots", 0)?;
+            attrs.number::<u8>("lines", 0)?;
+        }
+        "drawer" => {
+            attrs.drawer_pin()?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
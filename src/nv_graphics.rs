@@ -0,0 +1,245 @@
+use std::{fmt, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{commands::image::ESCPOSImage, EcoPrintError, EcoPrintResult, TransportTrait};
+
+/// Key code of a graphic stored in the printer NV memory, two ASCII chars from 32 to 126.
+/// e.g.:
+/// ```rust
+/// use eco_print::nv_graphics::NvKey;
+///
+/// let logo = NvKey::new("LG").unwrap();
+/// assert!(NvKey::new("LOGO").is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct NvKey(pub u8, pub u8);
+
+impl NvKey {
+    pub fn new(key: &str) -> EcoPrintResult<Self> {
+        match key.as_bytes() {
+            [kc1, kc2] if Self::is_valid(*kc1) && Self::is_valid(*kc2) => Ok(Self(*kc1, *kc2)),
+            _ => Err(EcoPrintError::Image(format!(
+                "NV key must be 2 ASCII chars, got {:?}",
+                key
+            ))),
+        }
+    }
+
+    fn is_valid(code: u8) -> bool {
+        (32..=126).contains(&code)
+    }
+}
+
+impl TryFrom<String> for NvKey {
+    type Error = EcoPrintError;
+
+    fn try_from(key: String) -> EcoPrintResult<Self> {
+        Self::new(&key)
+    }
+}
+
+impl From<NvKey> for String {
+    fn from(key: NvKey) -> Self {
+        key.to_string()
+    }
+}
+
+impl fmt::Display for NvKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.0 as char, self.1 as char)
+    }
+}
+
+/// NV graphics memory in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NvCapacity {
+    pub total: u32,
+    pub remaining: u32,
+}
+
+/// GS ( L / GS 8 L with m = 48, the length is encoded on 2 bytes or 4 bytes (GS 8 L) for big images.
+/// The length counts m and the parameters.
+fn graphics_command(params: &[u8]) -> Vec<u8> {
+    let len = params.len() + 1;
+    let mut escpos = match u16::try_from(len) {
+        Ok(len) => {
+            let [pl, ph] = len.to_le_bytes();
+            vec![0x1D, 0x28, 0x4C, pl, ph]
+        }
+        Err(_) => {
+            let [p1, p2, p3, p4] = (len as u32).to_le_bytes();
+            vec![0x1D, 0x38, 0x4C, p1, p2, p3, p4]
+        }
+    };
+    escpos.push(0x30);
+    escpos.extend(params);
+    escpos
+}
+
+/// GS ( L fn=67 - Define the image as a raster NV graphic with the key
+pub fn define_nv_graphic(key: NvKey, image: &ESCPOSImage) -> EcoPrintResult<Vec<u8>> {
+    if !(1..=8192).contains(&image.width()) || !(1..=2304).contains(&image.height()) {
+        return Err(EcoPrintError::Image(format!(
+            "NV graphic of {}x{} dots is out of 8192x2304",
+            image.width(),
+            image.height()
+        )));
+    }
+    let [xl, xh, ..] = image.width().to_le_bytes();
+    let [yl, yh, ..] = image.height().to_le_bytes();
+    let mut params = vec![0x43, 0x30, key.0, key.1, 0x01, xl, xh, yl, yh, 0x31];
+    params.extend(image.raster());
+    Ok(graphics_command(&params))
+}
+
+/// GS ( L fn=69 - Print the NV graphic with the key, scale 1 or 2 on each axis
+pub fn print_nv_graphic(key: NvKey, scale_x: u8, scale_y: u8) -> Vec<u8> {
+    graphics_command(&[0x45, key.0, key.1, scale_x.clamp(1, 2), scale_y.clamp(1, 2)])
+}
+
+/// GS ( L fn=66 - Delete the NV graphic with the key
+pub fn delete_nv_graphic(key: NvKey) -> Vec<u8> {
+    graphics_command(&[0x42, key.0, key.1])
+}
+
+/// GS ( L fn=65 - Delete every NV graphic
+pub fn delete_all_nv_graphics() -> Vec<u8> {
+    graphics_command(&[0x41, b'C', b'L', b'R'])
+}
+
+/// FS q n - Define the legacy NV bit images, replacing all the images stored before.
+/// The images are printed with FS p by their position, starting at 1.
+pub fn define_nv_bit_images(images: &[ESCPOSImage]) -> EcoPrintResult<Vec<u8>> {
+    if images.is_empty() || images.len() > 255 {
+        return Err(EcoPrintError::Image(format!(
+            "FS q defines 1 to 255 images, got {}",
+            images.len()
+        )));
+    }
+    let mut escpos = vec![0x1C, 0x71, images.len() as u8];
+    for image in images {
+        // Sizes are in units of 8 dots and the data is in columns of 8 vertical dots
+        let x = image.width_bytes();
+        let y = image.height().div_ceil(8);
+        if x > 1023 || y > 288 {
+            return Err(EcoPrintError::Image(format!(
+                "NV bit image of {}x{} dots is too big",
+                image.width(),
+                image.height()
+            )));
+        }
+        let [xl, xh, ..] = x.to_le_bytes();
+        let [yl, yh, ..] = y.to_le_bytes();
+        escpos.extend([xl, xh, yl, yh]);
+        for column in 0..x * 8 {
+            for y_byte in 0..y {
+                let mut byte = 0u8;
+                for bit in 0..8 {
+                    if image.is_black(column, y_byte * 8 + bit) {
+                        byte |= 0x80 >> bit;
+                    }
+                }
+                escpos.push(byte);
+            }
+        }
+    }
+    Ok(escpos)
+}
+
+/// FS p n m - Print the legacy NV bit image number n (starting at 1) in normal size
+pub fn print_nv_bit_image(n: u8) -> Vec<u8> {
+    vec![0x1C, 0x70, n, 0x00]
+}
+
+/// Read a GS ( L reply: 0x37, identifier, data, NUL
+async fn read_graphics_reply<T: TransportTrait + ?Sized>(
+    transport: &mut T,
+    identifier: u8,
+    timeout: Duration,
+) -> EcoPrintResult<Vec<u8>> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut reply = Vec::new();
+    loop {
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        reply.extend(transport.read(remaining).await?);
+
+        let Some(start) = reply
+            .windows(2)
+            .position(|header| header == [0x37, identifier])
+        else {
+            continue;
+        };
+        if let Some(len) = reply[start..].iter().position(|byte| *byte == 0x00) {
+            return Ok(reply[start + 2..start + len].to_vec());
+        }
+    }
+}
+
+fn parse_decimal(data: &[u8]) -> EcoPrintResult<u32> {
+    std::str::from_utf8(data)
+        .ok()
+        .and_then(|digits| digits.trim().parse().ok())
+        .ok_or_else(|| EcoPrintError::Image(format!("Invalid capacity reply {:?}", data)))
+}
+
+#[allow(async_fn_in_trait)]
+/// Graphics stored in the printer NV memory, so a logo is sent once instead of on every receipt.
+/// Print them with ESCPOSCommand::PrintNvGraphic (GS ( L) or ESCPOSCommand::PrintNvBitImage (FS p).
+pub trait NvGraphicsTrait {
+    /// Store the image with the key, replacing the graphic stored with the same key
+    async fn store_nv_graphic(&mut self, key: NvKey, image: &ESCPOSImage) -> EcoPrintResult<()>;
+    async fn delete_nv_graphic(&mut self, key: NvKey) -> EcoPrintResult<()>;
+    async fn delete_all_nv_graphics(&mut self) -> EcoPrintResult<()>;
+    /// Keys of the graphics stored in the printer
+    async fn list_nv_graphics(&mut self, timeout: Duration) -> EcoPrintResult<Vec<NvKey>>;
+    async fn nv_graphics_capacity(&mut self, timeout: Duration) -> EcoPrintResult<NvCapacity>;
+    /// Store the images with the legacy FS q, replacing all the legacy images stored before
+    async fn store_nv_bit_images(&mut self, images: &[ESCPOSImage]) -> EcoPrintResult<()>;
+}
+
+impl<T: TransportTrait> NvGraphicsTrait for T {
+    async fn store_nv_graphic(&mut self, key: NvKey, image: &ESCPOSImage) -> EcoPrintResult<()> {
+        self.write(&define_nv_graphic(key, image)?).await
+    }
+
+    async fn delete_nv_graphic(&mut self, key: NvKey) -> EcoPrintResult<()> {
+        self.write(&delete_nv_graphic(key)).await
+    }
+
+    async fn delete_all_nv_graphics(&mut self) -> EcoPrintResult<()> {
+        self.write(&delete_all_nv_graphics()).await
+    }
+
+    async fn list_nv_graphics(&mut self, timeout: Duration) -> EcoPrintResult<Vec<NvKey>> {
+        self.write(&graphics_command(&[0x40, b'K', b'C'])).await?;
+
+        let mut keys = Vec::new();
+        loop {
+            let data = read_graphics_reply(self, 0x72, timeout).await?;
+            // First byte is 0x40 on the last block, 0x41 when more keys follow after an ACK
+            let (more, codes) = match data.split_first() {
+                Some((status, codes)) => (*status == 0x41, codes),
+                None => (false, &data[..]),
+            };
+            keys.extend(codes.chunks_exact(2).map(|kc| NvKey(kc[0], kc[1])));
+            if !more {
+                return Ok(keys);
+            }
+            self.write(&[0x06]).await?;
+        }
+    }
+
+    async fn nv_graphics_capacity(&mut self, timeout: Duration) -> EcoPrintResult<NvCapacity> {
+        self.write(&graphics_command(&[0x00])).await?;
+        let total = parse_decimal(&read_graphics_reply(self, 0x30, timeout).await?)?;
+        self.write(&graphics_command(&[0x03])).await?;
+        let remaining = parse_decimal(&read_graphics_reply(self, 0x33, timeout).await?)?;
+        Ok(NvCapacity { total, remaining })
+    }
+
+    async fn store_nv_bit_images(&mut self, images: &[ESCPOSImage]) -> EcoPrintResult<()> {
+        self.write(&define_nv_bit_images(images)?).await
+    }
+}
@@ -0,0 +1,109 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::EcoPrintError;
+
+/// Identifier of a printer that survives app restarts.
+/// It can be stored in the app settings and used with FinderTrait::connect_by_id to connect without a full scan
+/// (only the BLE finder connects by id for now).
+/// The string form is `ble:<address>`, `usb:<vid>:<pid>[:<serial>]` (vid/pid in hex) or `tcp:<host>:<port>`.
+/// e.g.:
+/// ```rust
+/// use eco_print::PrinterId;
+///
+/// let id: PrinterId = "usb:0416:5011:A1B2".parse().unwrap();
+/// assert_eq!(
+///     id,
+///     PrinterId::Usb { vendor_id: 0x0416, product_id: 0x5011, serial: Some("A1B2".to_string()) }
+/// );
+/// assert_eq!(id.to_string(), "usb:0416:5011:A1B2");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "transport", rename_all = "snake_case")]
+pub enum PrinterId {
+    /// BLE MAC address, or the peripheral UUID on macOS/iOS where the address is hidden
+    Ble {
+        address: String,
+    },
+    Usb {
+        vendor_id: u16,
+        product_id: u16,
+        serial: Option<String>,
+    },
+    Network {
+        host: String,
+        port: u16,
+    },
+}
+
+impl PrinterId {
+    pub fn ble(address: impl Into<String>) -> Self {
+        Self::Ble {
+            address: address.into().to_uppercase(),
+        }
+    }
+}
+
+impl fmt::Display for PrinterId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrinterId::Ble { address } => write!(f, "ble:{}", address),
+            PrinterId::Usb {
+                vendor_id,
+                product_id,
+                serial,
+            } => {
+                write!(f, "usb:{:04x}:{:04x}", vendor_id, product_id)?;
+                if let Some(serial) = serial {
+                    write!(f, ":{}", serial)?;
+                }
+                Ok(())
+            }
+            PrinterId::Network { host, port } => write!(f, "tcp:{}:{}", host, port),
+        }
+    }
+}
+
+impl FromStr for PrinterId {
+    type Err = EcoPrintError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || EcoPrintError::PrinterId(s.to_string());
+        let (transport, rest) = s.split_once(':').ok_or_else(invalid)?;
+
+        match transport {
+            "ble" if !rest.is_empty() => Ok(PrinterId::ble(rest)),
+            "usb" => {
+                let mut parts = rest.splitn(3, ':');
+                let vendor_id = parts.next().ok_or_else(invalid)?;
+                let product_id = parts.next().ok_or_else(invalid)?;
+                Ok(PrinterId::Usb {
+                    vendor_id: u16::from_str_radix(vendor_id, 16).map_err(|_| invalid())?,
+                    product_id: u16::from_str_radix(product_id, 16).map_err(|_| invalid())?,
+                    serial: parts.next().map(str::to_string),
+                })
+            }
+            "tcp" => {
+                // rsplit so IPv6 hosts keep their colons
+                let (host, port) = rest.rsplit_once(':').ok_or_else(invalid)?;
+                if host.is_empty() {
+                    return Err(invalid());
+                }
+                Ok(PrinterId::Network {
+                    host: host.to_string(),
+                    port: port.parse().map_err(|_| invalid())?,
+                })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Printer found or connected on a session, ready to be stored in the app settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrinterHandle {
+    pub id: PrinterId,
+    /// Name advertised by the printer when the handle was created
+    pub name: Option<String>,
+}
@@ -0,0 +1,356 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commands::{codepage::TextEncoding, text::Font},
+    identify::PrinterIdentity,
+};
+
+/// Firmware family of the printer, decides the vendor specific commands
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrinterVendor {
+    #[default]
+    Generic,
+    Epson,
+    Xprinter,
+    Rongta,
+    Star,
+}
+
+/// Buzzer command supported by the printer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuzzerType {
+    /// No buzzer, beeps are skipped
+    #[default]
+    None,
+    /// ESC B n t - Xprinter/Rongta style firmware
+    EscB,
+    /// ESC ( A - Epson models with a built-in buzzer
+    Epson,
+    /// ESC GS BEL m t1 t2 - Star and Star compatible external buzzer
+    Star,
+}
+
+/// Cutter installed on the printer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CutterType {
+    /// Tear bar only, cuts just feed the paper to it
+    None,
+    #[default]
+    Full,
+    FullAndPartial,
+}
+
+/// Command used to cut the paper
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CutCommand {
+    /// GS V 65/66 n - The printer feeds to the cutting position itself
+    #[default]
+    FeedAndCut,
+    /// GS V 0/1 - Cut right away, the builder feeds cutter_feed_dots before it
+    Cut,
+    /// ESC i / ESC m - Old firmware, the builder feeds cutter_feed_dots before it
+    Legacy,
+}
+
+/// Capabilities of a printer model, used by ESCPOSBuilder to generate the right commands.
+/// Entries the printer doesn't support are replaced by a fallback when there is one,
+/// or rejected by ESCPOSBuilder::render.
+/// e.g.:
+/// ```rust
+/// use eco_print::commands::command::ESCPOSBuilder;
+/// use eco_print::profile::PrinterProfile;
+///
+/// let builder = ESCPOSBuilder::new(PrinterProfile::xprinter());
+/// let tm_t20 = PrinterProfile::find("TM-T20III").unwrap();
+/// assert_eq!(tm_t20.print_width_dots, 576);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrinterProfile {
+    pub name: String,
+    pub vendor: PrinterVendor,
+    pub buzzer: BuzzerType,
+    pub cutter: CutterType,
+    pub cut_command: CutCommand,
+    /// Dots to feed so the last printed line passes the cutter (or the tear bar).
+    /// With CutCommand::FeedAndCut it's the extra feed after the cutting position.
+    pub cutter_feed_dots: u8,
+    /// Code page selected with ESC t when a job is initialized
+    pub code_page: u8,
+    /// Text encoding selected when a job is initialized, double-byte encodings are in Kanji mode
+    pub encoding: TextEncoding,
+    /// Line spacing in dots set when a job is initialized, None keeps the printer default
+    pub line_spacing: Option<u8>,
+    /// Left margin in dots set when a job is initialized
+    pub left_margin_dots: u16,
+    /// Paper roll width, e.g.: 58 or 80
+    pub paper_width_mm: u8,
+    /// Printable width in dots
+    pub print_width_dots: u16,
+    /// Dots per inch, 203 on most thermal printers
+    pub dpi: u16,
+    /// Characters per line with font A
+    pub columns_font_a: u8,
+    /// Characters per line with font B
+    pub columns_font_b: u8,
+    /// Characters per line with font C, on the models that have it
+    pub columns_font_c: u8,
+    /// ESC t code pages supported, empty when unknown (every code page is allowed)
+    pub code_pages: Vec<u8>,
+    /// GS ( k QR codes
+    pub qr_code: bool,
+    /// GS k barcodes
+    pub barcode: bool,
+    /// GS v 0 raster images, ESC * bit images are sent when false
+    pub raster_image: bool,
+    /// ESC L page mode
+    pub page_mode: bool,
+}
+
+impl Default for PrinterProfile {
+    fn default() -> Self {
+        Self::generic()
+    }
+}
+
+/// ESC t code pages of Epson compatible firmware (TM-T20, TM-m30, TM-P20):
+/// 0-5, PC851 to Thai (11-26) and TCVN-3 to KZ-1048 (30-53), with PC862/PC864 and WPC1250-1258
+const EPSON_CODE_PAGES: [u8; 46] = [
+    0, 1, 2, 3, 4, 5, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 30, 31, 32,
+    33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53,
+];
+
+impl PrinterProfile {
+    /// Profile with only the commands supported by every ESC/POS printer, on 58mm paper
+    pub fn generic() -> Self {
+        Self {
+            name: "Generic".to_string(),
+            vendor: PrinterVendor::Generic,
+            buzzer: BuzzerType::None,
+            cutter: CutterType::Full,
+            cut_command: CutCommand::FeedAndCut,
+            cutter_feed_dots: 0,
+            code_page: 0,
+            encoding: TextEncoding::CodePage,
+            line_spacing: None,
+            left_margin_dots: 0,
+            paper_width_mm: 58,
+            print_width_dots: 384,
+            dpi: 203,
+            columns_font_a: 32,
+            columns_font_b: 42,
+            columns_font_c: 42,
+            code_pages: Vec::new(),
+            qr_code: true,
+            barcode: true,
+            raster_image: true,
+            page_mode: false,
+        }
+    }
+
+    pub fn epson() -> Self {
+        Self {
+            name: "Epson".to_string(),
+            vendor: PrinterVendor::Epson,
+            buzzer: BuzzerType::Epson,
+            cutter: CutterType::FullAndPartial,
+            code_pages: EPSON_CODE_PAGES.to_vec(),
+            page_mode: true,
+            ..Self::generic().with_paper_80mm()
+        }
+    }
+
+    pub fn xprinter() -> Self {
+        Self {
+            name: "Xprinter".to_string(),
+            vendor: PrinterVendor::Xprinter,
+            buzzer: BuzzerType::EscB,
+            ..Self::generic()
+        }
+    }
+
+    pub fn rongta() -> Self {
+        Self {
+            name: "Rongta".to_string(),
+            vendor: PrinterVendor::Rongta,
+            buzzer: BuzzerType::EscB,
+            cutter: CutterType::FullAndPartial,
+            ..Self::generic()
+        }
+    }
+
+    pub fn star() -> Self {
+        Self {
+            name: "Star".to_string(),
+            vendor: PrinterVendor::Star,
+            buzzer: BuzzerType::Star,
+            cutter: CutterType::FullAndPartial,
+            ..Self::generic().with_paper_80mm()
+        }
+    }
+
+    /// Epson TM-T20 series, 80mm with autocutter
+    pub fn epson_tm_t20() -> Self {
+        Self {
+            name: "TM-T20".to_string(),
+            buzzer: BuzzerType::None,
+            ..Self::epson()
+        }
+    }
+
+    /// Epson TM-m30 series, 80mm with autocutter and buzzer
+    pub fn epson_tm_m30() -> Self {
+        Self {
+            name: "TM-m30".to_string(),
+            ..Self::epson()
+        }
+    }
+
+    /// Epson TM-P20 mobile printer, 58mm with tear bar
+    pub fn epson_tm_p20() -> Self {
+        Self {
+            name: "TM-P20".to_string(),
+            buzzer: BuzzerType::None,
+            cutter: CutterType::None,
+            ..Self::epson().with_paper_58mm()
+        }
+    }
+
+    /// Xprinter XP-58 series, 58mm with tear bar
+    pub fn xprinter_xp58() -> Self {
+        Self {
+            name: "XP-58".to_string(),
+            cutter: CutterType::None,
+            ..Self::xprinter()
+        }
+    }
+
+    /// Xprinter XP-80 series, 80mm with autocutter
+    pub fn xprinter_xp80() -> Self {
+        Self {
+            name: "XP-80".to_string(),
+            ..Self::xprinter().with_paper_80mm()
+        }
+    }
+
+    /// Rongta RPP02 mobile printer, 58mm with tear bar and no buzzer
+    pub fn rongta_rpp02() -> Self {
+        Self {
+            name: "RPP02".to_string(),
+            buzzer: BuzzerType::None,
+            cutter: CutterType::None,
+            ..Self::rongta()
+        }
+    }
+
+    /// Star TSP100 series in ESC/POS mode, 80mm with autocutter
+    pub fn star_tsp100() -> Self {
+        Self {
+            name: "TSP1".to_string(),
+            ..Self::star()
+        }
+    }
+
+    /// Profiles of the models known by the library
+    pub fn bundled() -> Vec<Self> {
+        vec![
+            Self::epson_tm_t20(),
+            Self::epson_tm_m30(),
+            Self::epson_tm_p20(),
+            Self::xprinter_xp58(),
+            Self::xprinter_xp80(),
+            Self::rongta_rpp02(),
+            Self::star_tsp100(),
+        ]
+    }
+
+    /// Bundled profile of the model, the model name must start with the profile name (case insensitive),
+    /// e.g.: "TM-T20III" matches the "TM-T20" profile
+    pub fn find(model_name: &str) -> Option<Self> {
+        let model_name = model_name.trim().to_uppercase();
+        Self::bundled()
+            .into_iter()
+            .find(|profile| model_name.starts_with(&profile.name.to_uppercase()))
+    }
+
+    /// 58mm paper, 384 dots at 203 dpi
+    pub fn with_paper_58mm(mut self) -> Self {
+        self.paper_width_mm = 58;
+        self.print_width_dots = 384;
+        self.columns_font_a = 32;
+        self.columns_font_b = 42;
+        self.columns_font_c = 42;
+        self
+    }
+
+    /// 80mm paper, 576 dots at 203 dpi
+    pub fn with_paper_80mm(mut self) -> Self {
+        self.paper_width_mm = 80;
+        self.print_width_dots = 576;
+        self.columns_font_a = 48;
+        self.columns_font_b = 64;
+        self.columns_font_c = 64;
+        self
+    }
+
+    /// Printable width in mm
+    pub fn print_width_mm(&self) -> f32 {
+        self.print_width_dots as f32 * 25.4 / self.dpi as f32
+    }
+
+    /// Millimetres in dots at the DPI of the printer
+    pub fn mm_to_dots(&self, mm: f32) -> u16 {
+        (mm * self.dpi as f32 / 25.4)
+            .round()
+            .clamp(0.0, u16::MAX as f32) as u16
+    }
+
+    /// Characters per line with the font
+    pub fn columns(&self, font: Font) -> u8 {
+        match font {
+            Font::A => self.columns_font_a,
+            Font::B => self.columns_font_b,
+            Font::C => self.columns_font_c,
+        }
+    }
+
+    /// Check if ESC t n can be sent to the printer
+    pub fn supports_code_page(&self, code_page: u8) -> bool {
+        self.code_pages.is_empty() || self.code_pages.contains(&code_page)
+    }
+
+    /// Pick the profile matching the manufacturer and model name replied by GS I.
+    /// The cutter is disabled when the type ID says there is none.
+    pub fn from_identity(identity: &PrinterIdentity) -> Self {
+        let manufacturer = identity.manufacturer.as_deref().unwrap_or("");
+        let model_name = identity.model_name.as_deref().unwrap_or("");
+        let text = format!("{} {}", manufacturer, model_name).to_uppercase();
+        let model_name = model_name.to_uppercase();
+
+        let mut profile = if let Some(profile) = Self::find(&model_name) {
+            profile
+        } else if text.contains("EPSON") || model_name.starts_with("TM-") {
+            Self::epson()
+        } else if text.contains("XPRINTER") || model_name.starts_with("XP-") {
+            Self::xprinter()
+        } else if text.contains("RONGTA") || model_name.starts_with("RP") {
+            Self::rongta()
+        } else if text.contains("STAR") || model_name.starts_with("TSP") {
+            Self::star()
+        } else {
+            Self::generic()
+        };
+
+        if identity.has_cutter() == Some(false) {
+            profile.cutter = CutterType::None;
+        }
+        if !model_name.is_empty() {
+            profile.name = identity.model_name.clone().unwrap_or_default();
+        }
+        profile
+    }
+}
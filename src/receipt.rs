@@ -0,0 +1,423 @@
+use crate::{
+    commands::{
+        barcode::Barcode,
+        command::{CutMode, ESCPOSBuilder, ESCPOSBuilderTrait, ESCPOSCommand, ESCPOSDataBuilder},
+        image::ESCPOSImage,
+        qrcode::ESCPOSQRCode,
+        table::{ColumnWidth, Overflow, Table, TableColumn},
+        text::{Align, TextBlock},
+    },
+    nv_graphics::NvKey,
+    profile::PrinterProfile,
+};
+
+/// GS ! changes the size only, ESC ! (DoubleHeightOn) would also clear the emphasis
+const DOUBLE_HEIGHT: ESCPOSCommand = ESCPOSCommand::CharacterSize {
+    width: 1,
+    height: 2,
+};
+const NORMAL_SIZE: ESCPOSCommand = ESCPOSCommand::CharacterSize {
+    width: 1,
+    height: 1,
+};
+
+/// Logo printed at the top of the receipt
+#[derive(Debug, Clone)]
+pub enum ReceiptLogo {
+    Image(ESCPOSImage),
+    /// Graphic stored in the printer NV memory, see NvGraphicsTrait
+    Nv(NvKey),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReceiptHeader {
+    pub logo: Option<ReceiptLogo>,
+    pub store_name: String,
+    /// Address, phone, tax ID, ... one entry per line
+    pub store_info: Vec<String>,
+}
+
+/// Amounts are in minor units (cents), e.g.: 350 is 3.50
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineItem {
+    pub name: String,
+    pub quantity: f64,
+    pub unit_price: i64,
+    /// Discount on the whole line
+    pub discount: i64,
+}
+
+impl LineItem {
+    pub fn new(name: impl Into<String>, quantity: f64, unit_price: i64) -> Self {
+        Self {
+            name: name.into(),
+            quantity,
+            unit_price,
+            discount: 0,
+        }
+    }
+
+    pub fn with_discount(mut self, discount: i64) -> Self {
+        self.discount = discount;
+        self
+    }
+
+    /// Quantity times the unit price, before the discount
+    pub fn gross(&self) -> i64 {
+        (self.quantity * self.unit_price as f64).round() as i64
+    }
+
+    pub fn total(&self) -> i64 {
+        self.gross() - self.discount
+    }
+}
+
+/// Labelled amount in minor units, used for taxes and payments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptAmount {
+    pub label: String,
+    pub amount: i64,
+}
+
+impl ReceiptAmount {
+    pub fn new(label: impl Into<String>, amount: i64) -> Self {
+        Self {
+            label: label.into(),
+            amount,
+        }
+    }
+}
+
+/// Code printed at the bottom of the receipt, e.g.: order number or a link to the e-receipt
+#[derive(Debug, Clone)]
+pub enum ReceiptCode {
+    Barcode(Barcode),
+    QrCode(ESCPOSQRCode),
+}
+
+/// How the receipt looks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptStyle {
+    /// Prefix of the amounts, e.g.: "$"
+    pub currency: String,
+    /// Digits after the decimal separator of the amounts
+    pub decimals: u8,
+    pub decimal_separator: char,
+    /// Character of the lines between the blocks
+    pub separator: char,
+    /// Character between the item names and their amount
+    pub item_fill: char,
+    /// Label of the discount lines
+    pub discount_label: String,
+    pub subtotal_label: String,
+    pub total_label: String,
+    pub change_label: String,
+    /// Print the total in double height
+    pub total_double_height: bool,
+    /// Cut the paper at the end of the receipt
+    pub cut: Option<CutMode>,
+}
+
+impl Default for ReceiptStyle {
+    fn default() -> Self {
+        Self {
+            currency: String::new(),
+            decimals: 2,
+            decimal_separator: '.',
+            separator: '-',
+            item_fill: ' ',
+            discount_label: "Discount".to_string(),
+            subtotal_label: "Subtotal".to_string(),
+            total_label: "TOTAL".to_string(),
+            change_label: "Change".to_string(),
+            total_double_height: true,
+            cut: Some(CutMode::Full),
+        }
+    }
+}
+
+impl ReceiptStyle {
+    /// Format an amount in minor units, e.g.: -1250 is "-$12.50".
+    /// Decimals above 19 (the digits of the largest amount) are printed as 19.
+    pub fn format_amount(&self, amount: i64) -> String {
+        let sign = if amount < 0 { "-" } else { "" };
+        let amount = amount.unsigned_abs();
+        let decimals = self.decimals.min(19);
+        let scale = 10u64.pow(u32::from(decimals));
+        if decimals == 0 {
+            return format!("{}{}{}", sign, self.currency, amount);
+        }
+        format!(
+            "{}{}{}{}{:0width$}",
+            sign,
+            self.currency,
+            amount / scale,
+            self.decimal_separator,
+            amount % scale,
+            width = usize::from(decimals)
+        )
+    }
+}
+
+/// Receipt document, rendered into an ESCPOSBuilder for the printer profile.
+/// The lines are laid out on the columns of the profile, so the same receipt fits 58mm and 80mm paper.
+/// e.g.:
+/// ```rust
+/// use eco_print::commands::command::ESCPOSBuilderTrait;
+/// use eco_print::profile::PrinterProfile;
+/// use eco_print::receipt::*;
+///
+/// let mut receipt = Receipt::new("Corner Coffee");
+/// receipt.header.store_info.push("12 Main St.".to_string());
+/// receipt.add_item(LineItem::new("Latte", 2.0, 350));
+/// receipt.add_item(LineItem::new("Blueberry muffin", 1.0, 275).with_discount(50));
+/// receipt.add_tax(ReceiptAmount::new("VAT 10%", 93));
+/// receipt.add_payment(ReceiptAmount::new("Cash", 1500));
+/// receipt.footer = Some("Thank you!".to_string());
+///
+/// let builder = receipt.to_builder(PrinterProfile::xprinter_xp58());
+/// let escpos = builder.to_escpos_bytes();
+/// let contains = |needle: &[u8]| escpos.windows(needle.len()).any(|window| window == needle);
+///
+/// // Bold and double height (ESC E 1, GS ! 0x01) for the store name and the total
+/// assert!(contains(b"\x1B\x45\x01\x1D\x21\x01Corner Coffee\n\x1D\x21\x00\x1B\x45\x00"));
+/// assert!(contains(b"\x1B\x45\x01\x1D\x21\x01TOTAL                      10.18\n"));
+/// assert!(contains(b"Latte                       7.00\n"));
+/// assert!(contains(b"Change                      4.82\n"));
+///
+/// // Decimals are capped at the 19 digits of an amount
+/// let style = ReceiptStyle { decimals: 25, ..Default::default() };
+/// assert_eq!(style.format_amount(-5), "-0.0000000000000000005");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Receipt {
+    pub header: ReceiptHeader,
+    pub items: Vec<LineItem>,
+    pub taxes: Vec<ReceiptAmount>,
+    pub payments: Vec<ReceiptAmount>,
+    pub footer: Option<String>,
+    pub code: Option<ReceiptCode>,
+    pub style: ReceiptStyle,
+}
+
+impl Receipt {
+    pub fn new(store_name: impl Into<String>) -> Self {
+        Self {
+            header: ReceiptHeader {
+                store_name: store_name.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    pub fn add_item(&mut self, item: LineItem) {
+        self.items.push(item);
+    }
+
+    pub fn add_tax(&mut self, tax: ReceiptAmount) {
+        self.taxes.push(tax);
+    }
+
+    pub fn add_payment(&mut self, payment: ReceiptAmount) {
+        self.payments.push(payment);
+    }
+
+    /// Sum of the item totals
+    pub fn subtotal(&self) -> i64 {
+        self.items.iter().map(LineItem::total).sum()
+    }
+
+    /// Subtotal plus taxes
+    pub fn total(&self) -> i64 {
+        self.subtotal() + self.taxes.iter().map(|tax| tax.amount).sum::<i64>()
+    }
+
+    /// Payments minus the total, 0 when the payments don't cover it
+    pub fn change(&self) -> i64 {
+        let paid: i64 = self.payments.iter().map(|payment| payment.amount).sum();
+        (paid - self.total()).max(0)
+    }
+
+    /// New builder for the profile with the receipt
+    pub fn to_builder(&self, profile: PrinterProfile) -> ESCPOSBuilder {
+        let mut builder = ESCPOSBuilder::new(profile);
+        self.add_to(&mut builder);
+        builder
+    }
+
+    /// Add the receipt to the builder
+    pub fn add_to(&self, builder: &mut ESCPOSBuilder) {
+        builder.add_commands(self.header_commands());
+        builder.add_command(self.separator());
+        builder.add_command(ESCPOSDataBuilder::Table(self.items_table()));
+        builder.add_command(self.separator());
+        builder.add_commands(self.totals_commands());
+        if !self.payments.is_empty() {
+            builder.add_command(self.separator());
+            builder.add_command(ESCPOSDataBuilder::Table(self.payments_table()));
+        }
+        builder.add_commands(self.footer_commands());
+    }
+
+    fn header_commands(&self) -> Vec<ESCPOSDataBuilder> {
+        let mut cmds = vec![ESCPOSDataBuilder::Command(ESCPOSCommand::AlignCenter)];
+        match &self.header.logo {
+            Some(ReceiptLogo::Image(image)) => cmds.push(ESCPOSDataBuilder::Image(image.clone())),
+            Some(ReceiptLogo::Nv(key)) => {
+                cmds.push(ESCPOSDataBuilder::Command(ESCPOSCommand::PrintNvGraphic {
+                    key: *key,
+                    scale_x: 1,
+                    scale_y: 1,
+                }))
+            }
+            None => {}
+        }
+        if !self.header.store_name.is_empty() {
+            cmds.extend([
+                ESCPOSDataBuilder::Command(ESCPOSCommand::EmphasizeOn),
+                ESCPOSDataBuilder::Command(DOUBLE_HEIGHT),
+                ESCPOSDataBuilder::Wrapped(TextBlock::new(self.header.store_name.as_str())),
+                ESCPOSDataBuilder::Command(NORMAL_SIZE),
+                ESCPOSDataBuilder::Command(ESCPOSCommand::EmphasizeOff),
+            ]);
+        }
+        for info in &self.header.store_info {
+            cmds.push(ESCPOSDataBuilder::Wrapped(TextBlock::new(info.as_str())));
+        }
+        cmds.push(ESCPOSDataBuilder::Command(ESCPOSCommand::AlignLeft));
+        cmds
+    }
+
+    /// Line of separator characters as wide as the paper
+    fn separator(&self) -> ESCPOSDataBuilder {
+        let mut table = Table::new(vec![
+            TableColumn::new(ColumnWidth::Auto).with_fill(self.style.separator)
+        ]);
+        table.add_row(vec![""]);
+        ESCPOSDataBuilder::Table(table)
+    }
+
+    /// Table of a label column taking the line and a right aligned amount column
+    fn amounts_table(&self, fill: char) -> Table {
+        let amounts = self
+            .items
+            .iter()
+            .flat_map(|item| [item.gross(), -item.discount])
+            .chain(
+                self.taxes
+                    .iter()
+                    .chain(&self.payments)
+                    .map(|line| line.amount),
+            )
+            .chain([self.subtotal(), self.total(), self.change()]);
+        let amount_width = amounts
+            .map(|amount| self.style.format_amount(amount).chars().count())
+            .max()
+            .unwrap_or(0);
+        Table::new(vec![
+            TableColumn::new(ColumnWidth::Auto).with_fill(fill),
+            TableColumn::new(ColumnWidth::Fixed(amount_width)).with_align(Align::Right),
+        ])
+        .with_overflow(Overflow::Wrap)
+    }
+
+    fn items_table(&self) -> Table {
+        let style = &self.style;
+        let mut table = self.amounts_table(style.item_fill);
+        for item in &self.items {
+            table.add_row(vec![item.name.clone(), style.format_amount(item.gross())]);
+            if item.quantity != 1.0 {
+                table.add_row(vec![
+                    format!(
+                        "  {} x {}",
+                        item.quantity,
+                        style.format_amount(item.unit_price)
+                    ),
+                    String::new(),
+                ]);
+            }
+            if item.discount != 0 {
+                table.add_row(vec![
+                    format!("  {}", style.discount_label),
+                    style.format_amount(-item.discount),
+                ]);
+            }
+        }
+        table
+    }
+
+    fn totals_commands(&self) -> Vec<ESCPOSDataBuilder> {
+        let style = &self.style;
+        let mut cmds = Vec::new();
+        if !self.taxes.is_empty() {
+            let mut table = self.amounts_table(' ');
+            table.add_row(vec![
+                style.subtotal_label.clone(),
+                style.format_amount(self.subtotal()),
+            ]);
+            for tax in &self.taxes {
+                table.add_row(vec![tax.label.clone(), style.format_amount(tax.amount)]);
+            }
+            cmds.push(ESCPOSDataBuilder::Table(table));
+        }
+
+        let mut total = self.amounts_table(' ');
+        total.add_row(vec![
+            style.total_label.clone(),
+            style.format_amount(self.total()),
+        ]);
+        cmds.push(ESCPOSDataBuilder::Command(ESCPOSCommand::EmphasizeOn));
+        if style.total_double_height {
+            cmds.push(ESCPOSDataBuilder::Command(DOUBLE_HEIGHT));
+        }
+        cmds.push(ESCPOSDataBuilder::Table(total));
+        if style.total_double_height {
+            cmds.push(ESCPOSDataBuilder::Command(NORMAL_SIZE));
+        }
+        cmds.push(ESCPOSDataBuilder::Command(ESCPOSCommand::EmphasizeOff));
+        cmds
+    }
+
+    fn payments_table(&self) -> Table {
+        let style = &self.style;
+        let mut table = self.amounts_table(' ');
+        for payment in &self.payments {
+            table.add_row(vec![
+                payment.label.clone(),
+                style.format_amount(payment.amount),
+            ]);
+        }
+        if self.change() > 0 {
+            table.add_row(vec![
+                style.change_label.clone(),
+                style.format_amount(self.change()),
+            ]);
+        }
+        table
+    }
+
+    fn footer_commands(&self) -> Vec<ESCPOSDataBuilder> {
+        let mut cmds = vec![
+            ESCPOSDataBuilder::Command(ESCPOSCommand::LineFeed),
+            ESCPOSDataBuilder::Command(ESCPOSCommand::AlignCenter),
+        ];
+        if let Some(footer) = &self.footer {
+            cmds.push(ESCPOSDataBuilder::Wrapped(TextBlock::new(footer.as_str())));
+        }
+        match &self.code {
+            Some(ReceiptCode::Barcode(barcode)) => {
+                cmds.push(ESCPOSDataBuilder::Barcode(barcode.clone()))
+            }
+            Some(ReceiptCode::QrCode(qrcode)) => {
+                cmds.push(ESCPOSDataBuilder::QrCode(qrcode.clone()))
+            }
+            None => {}
+        }
+        cmds.push(ESCPOSDataBuilder::Command(ESCPOSCommand::AlignLeft));
+        if let Some(mode) = self.style.cut {
+            cmds.push(ESCPOSDataBuilder::Cut(mode));
+        }
+        cmds
+    }
+}
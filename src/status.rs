@@ -0,0 +1,339 @@
+use std::time::Duration;
+
+use crate::{EcoPrintError, EcoPrintResult, TransportTrait};
+
+/// Timeout used when the caller doesn't need a specific one
+pub const DEFAULT_STATUS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Real-time status request (DLE EOT n).
+/// Printer, Offline, Error and PaperRoll are supported by every ESC/POS printer,
+/// Ink and Peripheral only by some models.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusQuery {
+    /// DLE EOT 1 - online/offline and drawer kick-out connector
+    Printer,
+    /// DLE EOT 2 - cause of the offline state (cover, paper feed, paper end)
+    Offline,
+    /// DLE EOT 3 - cutter, unrecoverable and auto-recoverable errors
+    Error,
+    /// DLE EOT 4 - roll paper near-end and end sensors
+    PaperRoll,
+    /// DLE EOT 7 a - ink status of the ink `a` (1 or 2)
+    Ink(u8),
+    /// DLE EOT 8 3 - peripheral device status
+    Peripheral,
+}
+
+impl StatusQuery {
+    /// Queries needed to fill every field of PrinterStatus supported by all printers
+    pub const BASIC: [StatusQuery; 4] = [
+        StatusQuery::Printer,
+        StatusQuery::Offline,
+        StatusQuery::Error,
+        StatusQuery::PaperRoll,
+    ];
+
+    pub fn to_escpos(&self) -> Vec<u8> {
+        match *self {
+            StatusQuery::Printer => vec![0x10, 0x04, 0x01],
+            StatusQuery::Offline => vec![0x10, 0x04, 0x02],
+            StatusQuery::Error => vec![0x10, 0x04, 0x03],
+            StatusQuery::PaperRoll => vec![0x10, 0x04, 0x04],
+            StatusQuery::Ink(a) => vec![0x10, 0x04, 0x07, a],
+            StatusQuery::Peripheral => vec![0x10, 0x04, 0x08, 0x03],
+        }
+    }
+
+    /// Check if a byte has the fixed bits of a DLE EOT reply (0xx1xx10)
+    pub fn is_reply(byte: u8) -> bool {
+        byte & 0b1001_0011 == 0b0001_0010
+    }
+}
+
+/// Status of the printer built from the DLE EOT replies.
+/// Fields not covered by the queries sent are left with their default value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrinterStatus {
+    pub online: bool,
+    /// Drawer kick-out connector pin 3 is high, most drawers report open this way
+    pub drawer_open: bool,
+    pub cover_open: bool,
+    pub paper_feed_pressed: bool,
+    pub paper_near_end: bool,
+    pub paper_out: bool,
+    pub cutter_error: bool,
+    pub unrecoverable_error: bool,
+    pub auto_recoverable_error: bool,
+    pub ink_near_end: bool,
+    pub ink_out: bool,
+    /// Raw reply of the peripheral device status (DLE EOT 8 3), the bits are model specific
+    pub peripheral: Option<u8>,
+}
+
+impl PrinterStatus {
+    /// Build the status from an Automatic Status Back message (4 bytes sent after GS a).
+    /// e.g.:
+    /// ```rust
+    /// use eco_print::status::PrinterStatus;
+    ///
+    /// let status = PrinterStatus::from_asb(&[0x30, 0x00, 0x0C, 0x00]);
+    /// assert!(status.cover_open);
+    /// assert!(status.paper_out);
+    /// ```
+    pub fn from_asb(message: &[u8; 4]) -> Self {
+        let bit = |byte: usize, n: u8| message[byte] & (1 << n) != 0;
+        Self {
+            drawer_open: bit(0, 2),
+            online: !bit(0, 3),
+            cover_open: bit(0, 5),
+            paper_feed_pressed: bit(0, 6),
+            cutter_error: bit(1, 3),
+            unrecoverable_error: bit(1, 5),
+            auto_recoverable_error: bit(1, 6),
+            paper_near_end: bit(2, 0) && bit(2, 1),
+            paper_out: bit(2, 2) && bit(2, 3),
+            ..Default::default()
+        }
+    }
+
+    /// Apply the reply byte of a query to the status.
+    /// e.g.:
+    /// ```rust
+    /// use eco_print::status::{PrinterStatus, StatusQuery};
+    ///
+    /// let mut status = PrinterStatus::default();
+    /// status.apply(StatusQuery::PaperRoll, 0x72).unwrap();
+    /// assert!(status.paper_out);
+    /// ```
+    pub fn apply(&mut self, query: StatusQuery, reply: u8) -> EcoPrintResult<()> {
+        if !StatusQuery::is_reply(reply) {
+            return Err(EcoPrintError::Status(format!(
+                "Invalid reply 0x{:02x} to {:?}",
+                reply, query
+            )));
+        }
+        let bit = |n: u8| reply & (1 << n) != 0;
+
+        match query {
+            StatusQuery::Printer => {
+                self.drawer_open = bit(2);
+                self.online = !bit(3);
+                self.paper_feed_pressed = bit(6);
+            }
+            StatusQuery::Offline => {
+                self.cover_open = bit(2);
+                self.paper_feed_pressed = bit(3);
+                self.paper_out = bit(5);
+            }
+            StatusQuery::Error => {
+                self.cutter_error = bit(3);
+                self.unrecoverable_error = bit(5);
+                self.auto_recoverable_error = bit(6);
+            }
+            StatusQuery::PaperRoll => {
+                self.paper_near_end = bit(2) && bit(3);
+                self.paper_out = bit(5) && bit(6);
+            }
+            StatusQuery::Ink(_) => {
+                self.ink_near_end = bit(2);
+                self.ink_out = bit(3);
+            }
+            StatusQuery::Peripheral => self.peripheral = Some(reply),
+        }
+        Ok(())
+    }
+
+    /// Check if the printer can print right now
+    pub fn is_ready(&self) -> bool {
+        self.online
+            && !self.cover_open
+            && !self.paper_out
+            && !self.cutter_error
+            && !self.unrecoverable_error
+    }
+}
+
+/// Status changes sent automatically by the printer (GS a n).
+/// Combine the flags with `|`, AsbMask::NONE disables the Automatic Status Back.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AsbMask(pub u8);
+
+impl AsbMask {
+    pub const NONE: AsbMask = AsbMask(0x00);
+    /// Drawer kick-out connector pin 3
+    pub const DRAWER: AsbMask = AsbMask(0x01);
+    pub const ONLINE: AsbMask = AsbMask(0x02);
+    pub const ERROR: AsbMask = AsbMask(0x04);
+    /// Roll paper near-end and end sensors
+    pub const PAPER: AsbMask = AsbMask(0x08);
+    pub const ALL: AsbMask = AsbMask(0x0F);
+
+    pub fn to_escpos(&self) -> Vec<u8> {
+        vec![0x1D, 0x61, self.0]
+    }
+}
+
+impl std::ops::BitOr for AsbMask {
+    type Output = AsbMask;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        AsbMask(self.0 | rhs.0)
+    }
+}
+
+/// Data received from the printer, split by AsbDemux
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Incoming {
+    /// Automatic Status Back message
+    Asb(PrinterStatus),
+    /// Reply to a command (DLE EOT, GS I, ...)
+    Reply(Vec<u8>),
+}
+
+/// Split the bytes read from the printer into ASB messages and command replies.
+/// ASB messages start with a byte 0xx1xx00 followed by 3 bytes 0xx0xxxx and may arrive
+/// split across reads, so the demux keeps the incomplete message between calls.
+/// Replies framed as `_ ... NUL` (GS I text) or `0x37 ... NUL` (GS ( L) are passed through as they are.
+/// e.g.:
+/// ```rust
+/// use eco_print::status::{AsbDemux, Incoming, PrinterStatus};
+///
+/// let mut demux = AsbDemux::default();
+/// let online = PrinterStatus::from_asb(&[0x10, 0x00, 0x00, 0x00]);
+/// let paper_out = PrinterStatus::from_asb(&[0x10, 0x00, 0x0C, 0x00]);
+///
+/// // ASB message split across notifications
+/// assert_eq!(demux.push(&[0x10, 0x00]), vec![]);
+/// assert_eq!(demux.push(&[0x0C, 0x00]), vec![Incoming::Asb(paper_out)]);
+///
+/// // ASB messages and DLE EOT replies (0x16, 0x12) in the same notification
+/// assert_eq!(
+///     demux.push(&[0x16, 0x10, 0x00, 0x00, 0x00, 0x12]),
+///     vec![
+///         Incoming::Reply(vec![0x16]),
+///         Incoming::Asb(online),
+///         Incoming::Reply(vec![0x12]),
+///     ]
+/// );
+/// // DLE EOT reply followed by the start of an ASB message
+/// assert_eq!(demux.push(&[0x12, 0x10, 0x00]), vec![Incoming::Reply(vec![0x12])]);
+/// assert_eq!(demux.push(&[0x0C, 0x00]), vec![Incoming::Asb(paper_out)]);
+///
+/// // "0 DE" and "0@@@" look like ASB messages but are the text of GS I and GS ( L replies
+/// assert_eq!(demux.push(b"_P0 "), vec![Incoming::Reply(b"_P0 ".to_vec())]);
+/// assert_eq!(demux.push(b"DE\0"), vec![Incoming::Reply(b"DE\0".to_vec())]);
+/// assert_eq!(
+///     demux.push(b"\x370@@@\0\x10\0\0\0"),
+///     vec![Incoming::Reply(b"\x370@@@\0".to_vec()), Incoming::Asb(online)]
+/// );
+/// ```
+#[derive(Debug, Default)]
+pub struct AsbDemux {
+    asb: Vec<u8>,
+    in_text_block: bool,
+}
+
+impl AsbDemux {
+    pub fn push(&mut self, data: &[u8]) -> Vec<Incoming> {
+        let mut incoming = Vec::new();
+        let mut reply = Vec::new();
+
+        for &byte in data {
+            if self.in_text_block {
+                reply.push(byte);
+                self.in_text_block = byte != 0x00;
+                continue;
+            }
+
+            if !self.asb.is_empty() {
+                if byte & 0b1001_0000 == 0 {
+                    self.asb.push(byte);
+                    if self.asb.len() == 4 {
+                        let message = [self.asb[0], self.asb[1], self.asb[2], self.asb[3]];
+                        self.asb.clear();
+                        if !reply.is_empty() {
+                            incoming.push(Incoming::Reply(std::mem::take(&mut reply)));
+                        }
+                        incoming.push(Incoming::Asb(PrinterStatus::from_asb(&message)));
+                    }
+                    continue;
+                }
+                // Not an ASB message after all, the bytes belong to a reply
+                reply.append(&mut self.asb);
+            }
+
+            if byte == b'_' || byte == 0x37 {
+                self.in_text_block = true;
+                reply.push(byte);
+            } else if byte & 0b1001_0011 == 0b0001_0000 {
+                self.asb.push(byte);
+            } else {
+                reply.push(byte);
+            }
+        }
+
+        if !reply.is_empty() {
+            incoming.push(Incoming::Reply(reply));
+        }
+        incoming
+    }
+}
+
+#[allow(async_fn_in_trait)]
+/// Real-time status queries, available on every transport that can read the printer replies.
+pub trait StatusTrait {
+    /// Send a DLE EOT query and return the raw reply byte
+    async fn query_status(&mut self, query: StatusQuery, timeout: Duration) -> EcoPrintResult<u8>;
+
+    /// Send the queries and build the status from the replies
+    async fn status_of(
+        &mut self,
+        queries: &[StatusQuery],
+        timeout: Duration,
+    ) -> EcoPrintResult<PrinterStatus> {
+        let mut status = PrinterStatus::default();
+        for query in queries {
+            let reply = self.query_status(*query, timeout).await?;
+            status.apply(*query, reply)?;
+        }
+        Ok(status)
+    }
+
+    /// Send the basic queries (DLE EOT 1..4) and build the status from the replies
+    async fn status(&mut self, timeout: Duration) -> EcoPrintResult<PrinterStatus> {
+        self.status_of(&StatusQuery::BASIC, timeout).await
+    }
+
+    /// Enable the Automatic Status Back for the status in the mask, AsbMask::NONE disables it.
+    /// The messages are read by the transport, see ESCPOSPrinterBLE::status_events
+    async fn set_automatic_status(&mut self, mask: AsbMask) -> EcoPrintResult<()>;
+
+    /// Return PaperOut if the roll paper end sensor is set
+    async fn check_paper(&mut self, timeout: Duration) -> EcoPrintResult<()> {
+        let status = self.status_of(&[StatusQuery::PaperRoll], timeout).await?;
+        if status.paper_out {
+            return Err(EcoPrintError::PaperOut);
+        }
+        Ok(())
+    }
+}
+
+impl<T: TransportTrait> StatusTrait for T {
+    async fn set_automatic_status(&mut self, mask: AsbMask) -> EcoPrintResult<()> {
+        self.write(&mask.to_escpos()).await
+    }
+
+    async fn query_status(&mut self, query: StatusQuery, timeout: Duration) -> EcoPrintResult<u8> {
+        self.write(&query.to_escpos()).await?;
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            let reply = self.read(remaining).await?;
+            // Leftovers of previous commands are skipped until the reply shows up
+            if let Some(byte) = reply.into_iter().find(|byte| StatusQuery::is_reply(*byte)) {
+                return Ok(byte);
+            }
+        }
+    }
+}
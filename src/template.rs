@@ -0,0 +1,744 @@
+use std::borrow::Cow;
+
+use serde_json::Value;
+
+use crate::{
+    commands::command::{ESCPOSBuilder, ESCPOSBuilderTrait, ESCPOSDataBuilder},
+    markup::{self, Node, Position, TAGS},
+    EcoPrintError, EcoPrintResult,
+};
+
+/// Layout with placeholders, loops and conditionals, compiled once and rendered for each print
+/// against a serde_json Value.
+///
+/// Markup templates use the markup tags plus:
+/// - `{{ path }}` - Value at the path in text and attribute values, e.g.: `{{ store.name }}`, `{{ items.0.name }}`
+/// - `<each in=items as=item>...</each>` - Content repeated for each element of the array,
+///   `{{ @index }}` is the position of the element starting at 0
+/// - `<if test=path>...<else/>...</if>`, `<unless test=path>...</unless>` - Content printed when the value
+///   is truthy (not null, false, 0, "", [] or {})
+///
+/// JSON templates are JSON jobs (see json.rs) where strings can hold placeholders,
+/// a string that is only a placeholder is replaced by the value itself (e.g.: a number).
+/// The elements of the command arrays can also be:
+/// - `{ "each": "items", "as": "item", "commands": [...] }`
+/// - `{ "if": "path", "then": [...], "else": [...] }`
+///
+/// e.g.:
+/// ```rust
+/// use eco_print::commands::command::*;
+/// use eco_print::template::Template;
+/// use serde_json::json;
+///
+/// let template = Template::markup(
+///     r#"
+///     <center><b>{{ store }}</b></center><br/>
+///     <each in=items as=item>
+///       <row><col>{{ item.name }}</col><col width=8 align=right>{{ item.price }}</col></row>
+///     </each>
+///     <if test=paid>PAID<else/>PENDING</if><br/>
+///     <cut/>
+///     "#,
+/// )
+/// .unwrap();
+///
+/// let context = json!({
+///     "store": "Corner Coffee",
+///     "items": [{ "name": "Latte", "price": "3.50" }, { "name": "Muffin", "price": "2.75" }],
+///     "paid": true
+/// });
+/// let mut builder = ESCPOSBuilder::default();
+/// template.render_into(&context, &mut builder).unwrap();
+/// let escpos = builder.to_escpos_bytes();
+/// let contains = |needle: &[u8]| escpos.windows(needle.len()).any(|window| window == needle);
+/// assert!(contains(b"Corner Coffee"));
+/// assert!(contains(b"Latte                       3.50\nMuffin                      2.75\n"));
+/// assert!(contains(b"PAID\n") && !contains(b"PENDING"));
+///
+/// let template = Template::json(
+///     r#"[
+///       { "each": "items", "commands": [{ "text": "{{ item.name }} x{{ item.qty }}\n" }] },
+///       { "if": "paid", "then": [{ "cut": "full" }], "else": [{ "text": "PENDING\n" }] }
+///     ]"#,
+/// )
+/// .unwrap();
+/// let cmds = template
+///     .render(&json!({ "items": [{ "name": "Latte", "qty": 2 }], "paid": false }))
+///     .unwrap();
+/// assert!(matches!(&cmds[..], [
+///     ESCPOSDataBuilder::Text(latte),
+///     ESCPOSDataBuilder::Text(pending),
+/// ] if latte == "Latte x2\n" && pending == "PENDING\n"));
+///
+/// // Mistakes outside of the placeholders are found when compiling
+/// assert!(Template::json(r#"[{ "cut": "ful" }]"#).is_err());
+/// assert!(Template::markup("<each in=items><cut mode=ful/></each>").is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Template {
+    kind: TemplateKind,
+}
+
+#[derive(Debug, Clone)]
+enum TemplateKind {
+    Markup(Vec<TNode>),
+    Json(Vec<JItem<JCommand>>),
+}
+
+impl Template {
+    /// Compile a markup template, the syntax, the tags, the placeholders and the attribute values
+    /// without placeholders are checked here. Elements without placeholders are converted here too
+    /// (e.g.: `<img src=logo.png>` is read once), unless their conversion depends on the tags around them.
+    pub fn markup(markup: &str) -> EcoPrintResult<Self> {
+        let nodes = markup::parse_nodes(markup)?;
+        Ok(Self {
+            kind: TemplateKind::Markup(compile_nodes(&nodes, Place::Top)?),
+        })
+    }
+
+    /// Compile a JSON template, a job object or an array of commands.
+    /// Commands without placeholders are read here, the others must have the key of a command.
+    pub fn json(json: &str) -> EcoPrintResult<Self> {
+        let value: Value = serde_json::from_str(json)
+            .map_err(|_err| EcoPrintError::Template(format!("Invalid JSON - {}", _err)))?;
+        let commands = match value {
+            Value::Array(commands) => commands,
+            Value::Object(mut job) => match job.remove("commands") {
+                Some(Value::Array(commands)) => commands,
+                _ => return Err(EcoPrintError::Template("Job without commands".to_string())),
+            },
+            _ => {
+                return Err(EcoPrintError::Template(
+                    "Expected a job or an array of commands".to_string(),
+                ))
+            }
+        };
+        Ok(Self {
+            kind: TemplateKind::Json(compile_items(&commands, compile_command)?),
+        })
+    }
+
+    /// Builder entries for the context
+    pub fn render(&self, context: &Value) -> EcoPrintResult<Vec<ESCPOSDataBuilder>> {
+        let mut scope = Scope {
+            root: context,
+            loops: Vec::new(),
+        };
+        match &self.kind {
+            TemplateKind::Markup(nodes) => {
+                let mut rendered = Vec::new();
+                render_nodes(nodes, &mut scope, &mut rendered)?;
+                markup::to_commands(&rendered)
+            }
+            TemplateKind::Json(items) => {
+                let mut commands = Vec::new();
+                render_items(items, &mut scope, &mut commands)?;
+                Ok(commands)
+            }
+        }
+    }
+
+    /// Add the entries for the context to the builder
+    pub fn render_into(&self, context: &Value, builder: &mut ESCPOSBuilder) -> EcoPrintResult<()> {
+        builder.add_commands(self.render(context)?);
+        Ok(())
+    }
+}
+
+/// Dotted path to a value, e.g.: item.name or items.0
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Path(Vec<String>);
+
+impl Path {
+    fn parse(path: &str) -> Option<Self> {
+        let segments: Vec<String> = path.trim().split('.').map(str::to_string).collect();
+        let valid = segments.iter().all(|segment| {
+            !segment.is_empty()
+                && segment.chars().enumerate().all(|(i, c)| {
+                    c.is_alphanumeric() || c == '_' || c == '-' || (i == 0 && c == '@')
+                })
+        });
+        valid.then_some(Self(segments))
+    }
+}
+
+/// Text with placeholders
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Var(Path),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Interp(Vec<Segment>);
+
+impl Interp {
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut rest = text;
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                segments.push(Segment::Literal(rest[..start].to_string()));
+            }
+            let end = rest[start..]
+                .find("}}")
+                .ok_or_else(|| "Placeholder without }}".to_string())?;
+            let path = &rest[start + 2..start + end];
+            let path =
+                Path::parse(path).ok_or_else(|| format!("Invalid placeholder {{{{{}}}}}", path))?;
+            segments.push(Segment::Var(path));
+            rest = &rest[start + end + 2..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Literal(rest.to_string()));
+        }
+        Ok(Self(segments))
+    }
+
+    fn has_vars(&self) -> bool {
+        self.0
+            .iter()
+            .any(|segment| matches!(segment, Segment::Var(_)))
+    }
+
+    fn render(&self, scope: &Scope) -> String {
+        self.0
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(text) => text.clone(),
+                Segment::Var(path) => to_text(&scope.get(path)),
+            })
+            .collect()
+    }
+}
+
+/// Values visible while rendering: the context and the elements of the loops, borrowed from the context
+struct Scope<'a> {
+    root: &'a Value,
+    /// Innermost last
+    loops: Vec<Loop<'a>>,
+}
+
+struct Loop<'a> {
+    alias: &'a str,
+    value: &'a Value,
+    index: usize,
+}
+
+impl<'a> Scope<'a> {
+    /// Value at the path, `@index` is the position in the innermost loop
+    fn get(&self, path: &Path) -> Cow<'a, Value> {
+        if path.0[0] == "@index" {
+            return Cow::Owned(
+                self.loops
+                    .last()
+                    .map_or(Value::Null, |inner| inner.index.into()),
+            );
+        }
+        Cow::Borrowed(self.lookup(path))
+    }
+
+    fn lookup(&self, path: &Path) -> &'a Value {
+        let (first, rest) = path.0.split_first().expect("paths have a segment");
+        let (mut value, segments) = self
+            .loops
+            .iter()
+            .rev()
+            .find(|inner| inner.alias == first)
+            .map(|inner| (inner.value, rest))
+            .unwrap_or((self.root, &path.0[..]));
+
+        for segment in segments {
+            value = match value {
+                Value::Object(map) => map.get(segment),
+                Value::Array(array) => segment.parse::<usize>().ok().and_then(|i| array.get(i)),
+                _ => None,
+            }
+            .unwrap_or(&Value::Null);
+        }
+        value
+    }
+
+    /// Run `render` for each element of the array at the path
+    fn each<F>(&mut self, path: &Path, alias: &'a str, mut render: F) -> EcoPrintResult<()>
+    where
+        F: FnMut(&mut Self) -> EcoPrintResult<()>,
+    {
+        for (index, value) in items_of(self.lookup(path)).iter().enumerate() {
+            self.loops.push(Loop {
+                alias,
+                value,
+                index,
+            });
+            let result = render(self);
+            self.loops.pop();
+            result?;
+        }
+        Ok(())
+    }
+}
+
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(value) => *value,
+        Value::Number(number) => number.as_f64() != Some(0.0),
+        Value::String(text) => !text.is_empty(),
+        Value::Array(array) => !array.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
+
+fn items_of(value: &Value) -> &[Value] {
+    match value {
+        Value::Array(array) => array,
+        Value::Null => &[],
+        value => std::slice::from_ref(value),
+    }
+}
+
+/// Compiled markup node
+#[derive(Debug, Clone)]
+enum TNode {
+    Text(Interp, Position),
+    Element {
+        name: String,
+        attrs: Vec<(String, Interp)>,
+        children: Vec<TNode>,
+        pos: Position,
+    },
+    /// Static element converted when compiling
+    Commands(Vec<ESCPOSDataBuilder>, Position),
+    Each {
+        path: Path,
+        alias: String,
+        children: Vec<TNode>,
+    },
+    If {
+        path: Path,
+        negate: bool,
+        then: Vec<TNode>,
+        otherwise: Vec<TNode>,
+    },
+}
+
+impl TNode {
+    /// Rendered the same for every context
+    fn is_static(&self) -> bool {
+        match self {
+            TNode::Text(interp, _) => !interp.has_vars(),
+            TNode::Element {
+                attrs, children, ..
+            } => {
+                attrs.iter().all(|(_, value)| !value.has_vars())
+                    && children.iter().all(TNode::is_static)
+            }
+            TNode::Commands(..) => true,
+            TNode::Each { .. } | TNode::If { .. } => false,
+        }
+    }
+
+    /// Contains an alignment tag, whose restore waits for the next line start
+    fn has_align(&self) -> bool {
+        match self {
+            TNode::Element { name, children, .. } => {
+                ALIGN_TAGS.contains(&name.as_str()) || children.iter().any(TNode::has_align)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Tags that restore the alignment at the start of the line after them, so not converted alone
+const ALIGN_TAGS: [&str; 3] = ["left", "center", "right"];
+
+/// Tags whose conversion depends on the tags around them, to restore the style when they close
+const STYLE_TAGS: [&str; 10] = [
+    "left", "center", "right", "b", "u", "inv", "small", "dh", "dw", "size",
+];
+
+/// Where compiled nodes are, to know which static elements can be converted when compiling
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Place {
+    /// Outside of any tag (loops and conditionals aside)
+    Top,
+    /// In a style tag, the other tags are converted the same as outside
+    Styled,
+    /// In the tags with text or rows only, their content is converted with the tag
+    Content,
+}
+
+fn compile_nodes(nodes: &[Node], place: Place) -> EcoPrintResult<Vec<TNode>> {
+    nodes.iter().map(|node| compile_node(node, place)).collect()
+}
+
+fn compile_node(node: &Node, place: Place) -> EcoPrintResult<TNode> {
+    let (name, attrs, children, pos) = match node {
+        Node::Text { text, pos } => {
+            return Interp::parse(text)
+                .map(|interp| TNode::Text(interp, *pos))
+                .map_err(|_err| pos.error(_err))
+        }
+        Node::Commands { cmds, pos } => return Ok(TNode::Commands(cmds.clone(), *pos)),
+        Node::Element {
+            name,
+            attrs,
+            children,
+            pos,
+        } => (name.as_str(), attrs, children, *pos),
+    };
+    let attr = |attr: &str| {
+        attrs
+            .iter()
+            .find(|(name, _)| name == attr)
+            .map(|(_, value)| value.as_str())
+            .ok_or_else(|| pos.error(format!("<{}> needs the attribute {}", name, attr)))
+    };
+    let path =
+        |attr: &str| Path::parse(attr).ok_or_else(|| pos.error(format!("Invalid path {:?}", attr)));
+
+    match name {
+        "each" => Ok(TNode::Each {
+            path: path(attr("in")?)?,
+            alias: attr("as").unwrap_or("item").to_string(),
+            children: compile_nodes(children, place)?,
+        }),
+        "if" | "unless" => {
+            let split = children
+                .iter()
+                .position(|child| matches!(child, Node::Element { name, .. } if name == "else"));
+            let (then, otherwise) = match split {
+                Some(i) => (&children[..i], &children[i + 1..]),
+                None => (&children[..], &[][..]),
+            };
+            Ok(TNode::If {
+                path: path(attr("test")?)?,
+                negate: name == "unless",
+                then: compile_nodes(then, place)?,
+                otherwise: compile_nodes(otherwise, place)?,
+            })
+        }
+        "else" => Err(pos.error("<else/> is only allowed in <if> and <unless>")),
+        name if TAGS.contains(&name) => {
+            let style = STYLE_TAGS.contains(&name);
+            let element = TNode::Element {
+                name: name.to_string(),
+                attrs: attrs
+                    .iter()
+                    .map(|(name, value)| {
+                        Ok((
+                            name.clone(),
+                            Interp::parse(value).map_err(|_err| pos.error(_err))?,
+                        ))
+                    })
+                    .collect::<EcoPrintResult<_>>()?,
+                children: compile_nodes(
+                    children,
+                    if style { Place::Styled } else { Place::Content },
+                )?,
+                pos,
+            };
+            if !element.is_static() || element.has_align() {
+                if !attrs.iter().any(|(_, value)| value.contains("{{")) {
+                    markup::check_attributes(name, attrs, pos)?;
+                }
+                return Ok(element);
+            }
+            // Style tags are converted outside of any tag only, where there is no style to restore
+            match (place, style) {
+                (Place::Top, _) | (Place::Styled, false) => {
+                    let mut rendered = Vec::new();
+                    let mut scope = Scope {
+                        root: &Value::Null,
+                        loops: Vec::new(),
+                    };
+                    render_nodes(std::slice::from_ref(&element), &mut scope, &mut rendered)?;
+                    Ok(TNode::Commands(markup::to_commands(&rendered)?, pos))
+                }
+                _ => {
+                    markup::check_attributes(name, attrs, pos)?;
+                    Ok(element)
+                }
+            }
+        }
+        name => Err(pos.error(format!("Unknown tag <{}>", name))),
+    }
+}
+
+fn render_nodes<'a>(
+    nodes: &'a [TNode],
+    scope: &mut Scope<'a>,
+    rendered: &mut Vec<Node>,
+) -> EcoPrintResult<()> {
+    for node in nodes {
+        match node {
+            TNode::Text(interp, pos) => rendered.push(Node::Text {
+                text: interp.render(scope),
+                pos: *pos,
+            }),
+            TNode::Element {
+                name,
+                attrs,
+                children,
+                pos,
+            } => {
+                let mut element_children = Vec::new();
+                render_nodes(children, scope, &mut element_children)?;
+                rendered.push(Node::Element {
+                    name: name.clone(),
+                    attrs: attrs
+                        .iter()
+                        .map(|(name, value)| (name.clone(), value.render(scope)))
+                        .collect(),
+                    children: element_children,
+                    pos: *pos,
+                });
+            }
+            TNode::Commands(cmds, pos) => rendered.push(Node::Commands {
+                cmds: cmds.clone(),
+                pos: *pos,
+            }),
+            TNode::Each {
+                path,
+                alias,
+                children,
+            } => scope.each(path, alias, |scope| render_nodes(children, scope, rendered))?,
+            TNode::If {
+                path,
+                negate,
+                then,
+                otherwise,
+            } => {
+                let branch = if is_truthy(&scope.get(path)) != *negate {
+                    then
+                } else {
+                    otherwise
+                };
+                render_nodes(branch, scope, rendered)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Compiled JSON value
+#[derive(Debug, Clone)]
+enum JNode {
+    Literal(Value),
+    /// String that is only a placeholder, replaced by the value
+    Value(Path),
+    Text(Interp),
+    Array(Vec<JItem<JNode>>),
+    Object(Vec<(String, JNode)>),
+}
+
+impl JNode {
+    fn is_static(&self) -> bool {
+        match self {
+            JNode::Literal(_) => true,
+            JNode::Value(_) | JNode::Text(_) => false,
+            JNode::Array(items) => items
+                .iter()
+                .all(|item| matches!(item, JItem::Node(node) if node.is_static())),
+            JNode::Object(entries) => entries.iter().all(|(_, value)| value.is_static()),
+        }
+    }
+}
+
+/// Compiled builder entry of a JSON template
+#[derive(Debug, Clone)]
+enum JCommand {
+    /// Entry without placeholders, read when compiling
+    Static(ESCPOSDataBuilder),
+    Dynamic(JNode),
+}
+
+/// Element of a JSON array, a value or a builder entry
+#[derive(Debug, Clone)]
+enum JItem<T> {
+    Node(T),
+    Each {
+        path: Path,
+        alias: String,
+        items: Vec<JItem<T>>,
+    },
+    If {
+        path: Path,
+        then: Vec<JItem<T>>,
+        otherwise: Vec<JItem<T>>,
+    },
+}
+
+/// Keys of the ESCPOSDataBuilder variants in JSON jobs
+const COMMANDS: [&str; 11] = [
+    "command", "text", "image", "qr_code", "barcode", "beep", "cut", "page", "wrapped", "table",
+    "spacing",
+];
+
+fn template_error(message: String) -> EcoPrintError {
+    EcoPrintError::Template(message)
+}
+
+fn json_path(value: &Value) -> EcoPrintResult<Path> {
+    value
+        .as_str()
+        .and_then(Path::parse)
+        .ok_or_else(|| template_error(format!("Invalid path {}", value)))
+}
+
+fn json_items<T>(
+    value: Option<&Value>,
+    compile: fn(&Value) -> EcoPrintResult<T>,
+) -> EcoPrintResult<Vec<JItem<T>>> {
+    match value {
+        Some(Value::Array(items)) => compile_items(items, compile),
+        None => Ok(Vec::new()),
+        Some(value) => Err(template_error(format!(
+            "Expected an array, found {}",
+            value
+        ))),
+    }
+}
+
+fn compile_items<T>(
+    items: &[Value],
+    compile: fn(&Value) -> EcoPrintResult<T>,
+) -> EcoPrintResult<Vec<JItem<T>>> {
+    items
+        .iter()
+        .map(|item| match item {
+            Value::Object(map) if map.contains_key("each") => Ok(JItem::Each {
+                path: json_path(&map["each"])?,
+                alias: map
+                    .get("as")
+                    .and_then(Value::as_str)
+                    .unwrap_or("item")
+                    .to_string(),
+                items: json_items(map.get("commands"), compile)?,
+            }),
+            Value::Object(map) if map.contains_key("if") => Ok(JItem::If {
+                path: json_path(&map["if"])?,
+                then: json_items(map.get("then"), compile)?,
+                otherwise: json_items(map.get("else"), compile)?,
+            }),
+            value => compile(value).map(JItem::Node),
+        })
+        .collect()
+}
+
+fn compile_json(value: &Value) -> EcoPrintResult<JNode> {
+    Ok(match value {
+        Value::String(text) => {
+            let interp = Interp::parse(text).map_err(template_error)?;
+            match interp.0.as_slice() {
+                [Segment::Var(path)] => JNode::Value(path.clone()),
+                _ if interp.has_vars() => JNode::Text(interp),
+                _ => JNode::Literal(value.clone()),
+            }
+        }
+        Value::Array(items) => JNode::Array(compile_items(items, compile_json)?),
+        Value::Object(map) => JNode::Object(
+            map.iter()
+                .map(|(key, value)| Ok((key.clone(), compile_json(value)?)))
+                .collect::<EcoPrintResult<_>>()?,
+        ),
+        value => JNode::Literal(value.clone()),
+    })
+}
+
+/// Entries without placeholders are read here,
+/// the others are checked to be an object with the key of an entry
+fn compile_command(value: &Value) -> EcoPrintResult<JCommand> {
+    let node = compile_json(value)?;
+    if node.is_static() {
+        return serde_json::from_value(value.clone())
+            .map(JCommand::Static)
+            .map_err(|_err| template_error(format!("Invalid command {} - {}", value, _err)));
+    }
+    match &node {
+        JNode::Object(entries)
+            if entries.len() == 1 && COMMANDS.contains(&entries[0].0.as_str()) =>
+        {
+            Ok(JCommand::Dynamic(node))
+        }
+        _ => Err(template_error(format!("Invalid command {}", value))),
+    }
+}
+
+/// Compiled JSON element rendered for a context
+trait Render {
+    type Output;
+
+    fn render<'a>(&'a self, scope: &mut Scope<'a>) -> EcoPrintResult<Self::Output>;
+}
+
+impl Render for JNode {
+    type Output = Value;
+
+    fn render<'a>(&'a self, scope: &mut Scope<'a>) -> EcoPrintResult<Value> {
+        Ok(match self {
+            JNode::Literal(value) => value.clone(),
+            JNode::Value(path) => scope.get(path).into_owned(),
+            JNode::Text(interp) => Value::String(interp.render(scope)),
+            JNode::Array(items) => {
+                let mut array = Vec::new();
+                render_items(items, scope, &mut array)?;
+                Value::Array(array)
+            }
+            JNode::Object(entries) => Value::Object(
+                entries
+                    .iter()
+                    .map(|(key, value)| Ok((key.clone(), value.render(scope)?)))
+                    .collect::<EcoPrintResult<_>>()?,
+            ),
+        })
+    }
+}
+
+impl Render for JCommand {
+    type Output = ESCPOSDataBuilder;
+
+    fn render<'a>(&'a self, scope: &mut Scope<'a>) -> EcoPrintResult<ESCPOSDataBuilder> {
+        match self {
+            JCommand::Static(cmd) => Ok(cmd.clone()),
+            JCommand::Dynamic(node) => serde_json::from_value(node.render(scope)?)
+                .map_err(|_err| template_error(format!("Invalid command - {}", _err))),
+        }
+    }
+}
+
+fn render_items<'a, T: Render>(
+    items: &'a [JItem<T>],
+    scope: &mut Scope<'a>,
+    rendered: &mut Vec<T::Output>,
+) -> EcoPrintResult<()> {
+    for item in items {
+        match item {
+            JItem::Node(node) => rendered.push(node.render(scope)?),
+            JItem::Each { path, alias, items } => {
+                scope.each(path, alias, |scope| render_items(items, scope, rendered))?
+            }
+            JItem::If {
+                path,
+                then,
+                otherwise,
+            } => {
+                let branch = if is_truthy(&scope.get(path)) {
+                    then
+                } else {
+                    otherwise
+                };
+                render_items(branch, scope, rendered)?;
+            }
+        }
+    }
+    Ok(())
+}
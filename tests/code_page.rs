@@ -0,0 +1,52 @@
+use eco_print::{
+    commands::codepage::TextEncoding,
+    commands::command::{ESCPOSBuilder, ESCPOSBuilderTrait, ESCPOSCommand, ESCPOSDataBuilder},
+    profile::PrinterProfile,
+};
+
+fn render(profile: PrinterProfile, code_page: u8, text: &str) -> Vec<u8> {
+    let mut builder = ESCPOSBuilder::new(profile);
+    builder.add_command(ESCPOSDataBuilder::Command(ESCPOSCommand::CodePage(
+        code_page,
+    )));
+    builder.add_command(ESCPOSDataBuilder::Text(text.to_string()));
+    builder.to_escpos_bytes()
+}
+
+#[test]
+fn epson_prints_hebrew_with_pc862() {
+    // Right aligned in visual order, PC862 has the Hebrew letters from 0x80
+    assert_eq!(
+        render(PrinterProfile::epson_tm_t20(), 36, "שלום\n"),
+        vec![0x1B, 0x74, 36, 0x1B, 0x61, 0x02, 0x8D, 0x85, 0x8C, 0x99, 0x0A]
+    );
+}
+
+#[test]
+fn epson_models_select_the_hebrew_and_arabic_code_pages() {
+    for profile in [
+        PrinterProfile::epson_tm_t20(),
+        PrinterProfile::epson_tm_m30(),
+        PrinterProfile::epson_tm_p20(),
+    ] {
+        for code_page in [36, 37, 49, 50] {
+            let escpos = render(profile.clone(), code_page, "");
+            assert_eq!(escpos, vec![0x1B, 0x74, code_page], "{}", profile.name);
+        }
+    }
+}
+
+#[test]
+fn leaving_utf8_selects_the_one_byte_encoding() {
+    let mut builder = ESCPOSBuilder::default();
+    for encoding in [TextEncoding::Utf8, TextEncoding::CodePage] {
+        builder.add_command(ESCPOSDataBuilder::Command(ESCPOSCommand::Encoding(
+            encoding,
+        )));
+        builder.add_command(ESCPOSDataBuilder::Text("é\n".to_string()));
+    }
+    assert_eq!(
+        builder.to_escpos_bytes(),
+        b"\x1C\x28\x43\x02\x00\x30\x02\xC3\xA9\n\x1C\x28\x43\x02\x00\x30\x01\x1C\x2E\x82\n"
+    );
+}
@@ -0,0 +1,33 @@
+use std::{collections::VecDeque, time::Duration};
+
+use eco_print::{EcoPrintError, EcoPrintResult, TransportTrait};
+
+/// Transport that records the bytes written and returns fixed replies,
+/// a read without reply left fails like a printer that doesn't answer
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    pub written: Vec<Vec<u8>>,
+    replies: VecDeque<Vec<u8>>,
+}
+
+impl MockTransport {
+    pub fn new(replies: &[&[u8]]) -> Self {
+        Self {
+            written: Vec::new(),
+            replies: replies.iter().map(|reply| reply.to_vec()).collect(),
+        }
+    }
+}
+
+impl TransportTrait for MockTransport {
+    async fn write(&mut self, data: &[u8]) -> EcoPrintResult<()> {
+        self.written.push(data.to_vec());
+        Ok(())
+    }
+
+    async fn read(&mut self, timeout: Duration) -> EcoPrintResult<Vec<u8>> {
+        self.replies.pop_front().ok_or_else(|| {
+            EcoPrintError::Timeout(format!("No reply from printer after {:?}", timeout))
+        })
+    }
+}
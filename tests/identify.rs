@@ -0,0 +1,80 @@
+mod common;
+
+use std::time::Duration;
+
+use common::MockTransport;
+use eco_print::{
+    identify::{IdentifyTrait, IdentityQuery, IdentityReply, PrinterIdentity},
+    EcoPrintError,
+};
+
+const TIMEOUT: Duration = Duration::from_millis(100);
+
+#[tokio::test]
+async fn query_identity_sends_gs_i_and_returns_the_byte() {
+    let mut transport = MockTransport::new(&[&[0x20]]);
+    let reply = transport
+        .query_identity(IdentityQuery::ModelId, TIMEOUT)
+        .await
+        .unwrap();
+    assert_eq!(reply, IdentityReply::Byte(0x20));
+    assert_eq!(transport.written, vec![vec![0x1D, 0x49, 0x01]]);
+}
+
+#[tokio::test]
+async fn query_identity_reads_the_text_split_across_reads() {
+    // Leftover byte before the `_` header, NUL in the second read
+    let mut transport = MockTransport::new(&[b"\x12_EP", b"SON\0"]);
+    let reply = transport
+        .query_identity(IdentityQuery::Manufacturer, TIMEOUT)
+        .await
+        .unwrap();
+    assert_eq!(reply, IdentityReply::Text("EPSON".to_string()));
+    assert_eq!(transport.written, vec![vec![0x1D, 0x49, 0x42]]);
+}
+
+#[tokio::test]
+async fn identify_leaves_the_queries_without_reply_as_none() {
+    // ModelName and Serial are not replied
+    let mut transport = MockTransport::new(&[&[0x20], &[0x02], &[0x01], b"_1.00\0", b"_EPSON\0"]);
+    let identity = transport.identify(TIMEOUT).await.unwrap();
+    assert_eq!(
+        transport.written,
+        IdentityQuery::ALL
+            .iter()
+            .map(IdentityQuery::to_escpos)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        identity,
+        PrinterIdentity {
+            model_id: Some(0x20),
+            type_id: Some(0x02),
+            version_id: Some(0x01),
+            firmware: Some("1.00".to_string()),
+            manufacturer: Some("EPSON".to_string()),
+            model_name: None,
+            serial: None,
+        }
+    );
+    assert_eq!(identity.has_cutter(), Some(true));
+}
+
+#[tokio::test]
+async fn query_identity_times_out_without_nul() {
+    let mut transport = MockTransport::new(&[b"_EPS"]);
+    let result = transport
+        .query_identity(IdentityQuery::Manufacturer, TIMEOUT)
+        .await;
+    assert!(matches!(result, Err(EcoPrintError::Timeout(_))));
+}
+
+#[tokio::test]
+async fn malformed_text_reply_is_not_taken_as_the_identity() {
+    // Text without the `_` header, the query keeps waiting until the timeout
+    let mut transport = MockTransport::new(&[b"EPSON\0"]);
+    let result = transport
+        .query_identity(IdentityQuery::Manufacturer, TIMEOUT)
+        .await;
+    assert!(matches!(result, Err(EcoPrintError::Timeout(_))));
+}
@@ -0,0 +1,60 @@
+use eco_print::{
+    commands::command::{ESCPOSBuilder, ESCPOSBuilderTrait, ESCPOSDataBuilder},
+    markup,
+    profile::PrinterProfile,
+    template::Template,
+};
+use serde_json::json;
+
+fn render(cmds: Vec<ESCPOSDataBuilder>) -> Vec<u8> {
+    let mut builder = ESCPOSBuilder::new(PrinterProfile::default());
+    for cmd in cmds {
+        builder.add_command(cmd);
+    }
+    builder.to_escpos_bytes()
+}
+
+#[test]
+fn alignment_is_restored_after_the_line_feed() {
+    // ESC a is ignored in the middle of a line, the left alignment comes after the LF
+    let cmds = markup::parse("<center>Title</center><br/>Next<br/>").unwrap();
+    assert_eq!(
+        render(cmds),
+        b"\x1B\x61\x01Title\n\x1B\x61\x00Next\n".to_vec()
+    );
+}
+
+#[test]
+fn alignment_is_restored_at_once_on_a_line_start() {
+    let cmds = markup::parse("<right>Total<br/></right>Thanks<br/>").unwrap();
+    assert_eq!(
+        render(cmds),
+        b"\x1B\x61\x02Total\n\x1B\x61\x00Thanks\n".to_vec()
+    );
+}
+
+#[test]
+fn nested_alignment_restores_the_outer_one() {
+    let cmds = markup::parse("<center><right>A</right><br/>B<br/></center>C<br/>").unwrap();
+    assert_eq!(
+        render(cmds),
+        b"\x1B\x61\x01\x1B\x61\x02A\n\x1B\x61\x01B\n\x1B\x61\x00C\n".to_vec()
+    );
+}
+
+#[test]
+fn template_restores_alignment_after_the_line_feed() {
+    let template = Template::markup("<center>{{title}}</center><br/>Next<br/>").unwrap();
+    let cmds = template.render(&json!({ "title": "Title" })).unwrap();
+    assert_eq!(
+        render(cmds),
+        b"\x1B\x61\x01Title\n\x1B\x61\x00Next\n".to_vec()
+    );
+
+    let template = Template::markup("<center>Title</center><br/>Next<br/>").unwrap();
+    let cmds = template.render(&json!({})).unwrap();
+    assert_eq!(
+        render(cmds),
+        b"\x1B\x61\x01Title\n\x1B\x61\x00Next\n".to_vec()
+    );
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use std::time::Duration;
+
+use common::MockTransport;
+use eco_print::{
+    commands::image::ESCPOSImage,
+    nv_graphics::{self, NvCapacity, NvGraphicsTrait, NvKey},
+};
+
+const TIMEOUT: Duration = Duration::from_millis(100);
+
+fn logo() -> NvKey {
+    NvKey::new("LG").unwrap()
+}
+
+#[test]
+fn define_counts_m_in_the_length() {
+    let image = ESCPOSImage::from_raster(8, 1, vec![0xFF]).unwrap();
+    assert_eq!(
+        nv_graphics::define_nv_graphic(logo(), &image).unwrap(),
+        vec![
+            0x1D, 0x28, 0x4C, 0x0C, 0x00, 0x30, 0x43, 0x30, b'L', b'G', 0x01, 0x08, 0x00, 0x01,
+            0x00, 0x31, 0xFF
+        ]
+    );
+}
+
+#[test]
+fn define_big_image_uses_gs_8_l() {
+    // 10 parameter bytes and 65536 raster bytes, plus m
+    let image = ESCPOSImage::from_raster(8192, 64, vec![0x00; 1024 * 64]).unwrap();
+    let escpos = nv_graphics::define_nv_graphic(logo(), &image).unwrap();
+    assert_eq!(
+        escpos[..8],
+        [0x1D, 0x38, 0x4C, 0x0B, 0x00, 0x01, 0x00, 0x30]
+    );
+    assert_eq!(escpos.len(), 7 + 0x1000B);
+}
+
+#[test]
+fn print_counts_m_in_the_length() {
+    assert_eq!(
+        nv_graphics::print_nv_graphic(logo(), 1, 2),
+        vec![0x1D, 0x28, 0x4C, 0x06, 0x00, 0x30, 0x45, b'L', b'G', 0x01, 0x02]
+    );
+}
+
+#[test]
+fn delete_counts_m_in_the_length() {
+    assert_eq!(
+        nv_graphics::delete_nv_graphic(logo()),
+        vec![0x1D, 0x28, 0x4C, 0x04, 0x00, 0x30, 0x42, b'L', b'G']
+    );
+    assert_eq!(
+        nv_graphics::delete_all_nv_graphics(),
+        vec![0x1D, 0x28, 0x4C, 0x05, 0x00, 0x30, 0x41, b'C', b'L', b'R']
+    );
+}
+
+#[tokio::test]
+async fn capacity_reads_the_total_and_remaining_replies() {
+    // The remaining reply arrives in two notifications
+    let mut printer = MockTransport::new(&[b"\x37\x301024\0", b"\x37\x33", b"512\0"]);
+    assert_eq!(
+        printer.nv_graphics_capacity(TIMEOUT).await.unwrap(),
+        NvCapacity {
+            total: 1024,
+            remaining: 512
+        }
+    );
+    assert_eq!(
+        printer.written,
+        vec![
+            vec![0x1D, 0x28, 0x4C, 0x02, 0x00, 0x30, 0x00],
+            vec![0x1D, 0x28, 0x4C, 0x02, 0x00, 0x30, 0x03],
+        ]
+    );
+}
+
+#[tokio::test]
+async fn capacity_fails_on_a_malformed_reply() {
+    let mut printer = MockTransport::new(&[b"\x37\x30ABC\0"]);
+    assert!(printer.nv_graphics_capacity(TIMEOUT).await.is_err());
+}
+
+#[tokio::test]
+async fn capacity_fails_without_reply() {
+    let mut printer = MockTransport::new(&[]);
+    assert!(matches!(
+        printer.nv_graphics_capacity(TIMEOUT).await,
+        Err(eco_print::EcoPrintError::Timeout(_))
+    ));
+}
+
+#[tokio::test]
+async fn list_reads_every_block_of_keys() {
+    // 0x41 announces more keys after the ACK, 0x40 is the last block
+    let mut printer = MockTransport::new(&[b"\x37\x72\x41LGA1\0", b"\x37\x72\x40B2\0"]);
+    assert_eq!(
+        printer.list_nv_graphics(TIMEOUT).await.unwrap(),
+        vec![
+            NvKey::new("LG").unwrap(),
+            NvKey::new("A1").unwrap(),
+            NvKey::new("B2").unwrap()
+        ]
+    );
+    assert_eq!(
+        printer.written,
+        vec![
+            vec![0x1D, 0x28, 0x4C, 0x04, 0x00, 0x30, 0x40, b'K', b'C'],
+            vec![0x06],
+        ]
+    );
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use std::time::Duration;
+
+use common::MockTransport;
+use eco_print::{
+    status::{PrinterStatus, StatusQuery, StatusTrait},
+    EcoPrintError,
+};
+
+const TIMEOUT: Duration = Duration::from_millis(100);
+
+#[tokio::test]
+async fn query_status_sends_dle_eot_and_returns_the_reply() {
+    let mut transport = MockTransport::new(&[&[0x72]]);
+    let reply = transport
+        .query_status(StatusQuery::PaperRoll, TIMEOUT)
+        .await
+        .unwrap();
+    assert_eq!(reply, 0x72);
+    assert_eq!(transport.written, vec![vec![0x10, 0x04, 0x04]]);
+}
+
+#[tokio::test]
+async fn query_status_skips_the_leftovers_of_previous_commands() {
+    // 0x00 and 0x41 don't have the fixed bits of a DLE EOT reply
+    let mut transport = MockTransport::new(&[&[0x00, 0x41], &[0x16]]);
+    let reply = transport
+        .query_status(StatusQuery::Printer, TIMEOUT)
+        .await
+        .unwrap();
+    assert_eq!(reply, 0x16);
+}
+
+#[tokio::test]
+async fn status_of_builds_the_status_from_the_replies() {
+    let mut transport = MockTransport::new(&[&[0x16], &[0x16], &[0x12], &[0x12]]);
+    let status = transport
+        .status_of(&StatusQuery::BASIC, TIMEOUT)
+        .await
+        .unwrap();
+    assert_eq!(
+        transport.written,
+        vec![
+            vec![0x10, 0x04, 0x01],
+            vec![0x10, 0x04, 0x02],
+            vec![0x10, 0x04, 0x03],
+            vec![0x10, 0x04, 0x04],
+        ]
+    );
+    assert_eq!(
+        status,
+        PrinterStatus {
+            online: true,
+            drawer_open: true,
+            cover_open: true,
+            ..Default::default()
+        }
+    );
+    assert!(!status.is_ready());
+}
+
+#[tokio::test]
+async fn check_paper_fails_when_the_paper_is_out() {
+    let mut transport = MockTransport::new(&[&[0x72]]);
+    let result = transport.check_paper(TIMEOUT).await;
+    assert!(matches!(result, Err(EcoPrintError::PaperOut)));
+}
+
+#[tokio::test]
+async fn query_status_times_out_without_reply() {
+    let mut transport = MockTransport::new(&[]);
+    let result = transport.query_status(StatusQuery::Printer, TIMEOUT).await;
+    assert!(matches!(result, Err(EcoPrintError::Timeout(_))));
+}
+
+#[tokio::test]
+async fn malformed_reply_is_not_taken_as_the_status() {
+    // 0xFF is not a DLE EOT reply, the query keeps waiting until the timeout
+    let mut transport = MockTransport::new(&[&[0xFF]]);
+    let result = transport.status_of(&[StatusQuery::Error], TIMEOUT).await;
+    assert!(matches!(result, Err(EcoPrintError::Timeout(_))));
+}
@@ -0,0 +1,19 @@
+use eco_print::commands::table::{ColumnWidth, Table, TableColumn};
+
+#[test]
+fn oversized_fixed_column_is_clamped_to_the_line() {
+    // Widths from JSON may be anything, scaling them must not overflow
+    let mut table = Table::new(vec![
+        TableColumn::new(ColumnWidth::Fixed(usize::MAX)),
+        TableColumn::new(ColumnWidth::Fixed(usize::MAX)),
+        TableColumn::new(ColumnWidth::Auto),
+    ]);
+    table.add_row(vec!["Coffee", "3.50", "x"]);
+    let widths = table.column_widths(32);
+    assert_eq!(widths, vec![15, 15, 0]);
+
+    let lines = table.to_lines(32);
+    assert!(lines
+        .iter()
+        .all(|line| line.trim_end_matches('\n').len() <= 32));
+}